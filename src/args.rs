@@ -1,6 +1,10 @@
 use std::path::PathBuf;
 
-use clap::{Args as ClapArgs, Parser, Subcommand as ClapSubcommand};
+use chrono::NaiveDate;
+
+use clap::{ArgGroup, Args as ClapArgs, Parser, Subcommand as ClapSubcommand, ValueEnum};
+
+use crate::theme::Theme;
 
 /// A simple CLI app to help plain-text note-taking.
 #[derive(Debug, Parser)]
@@ -21,16 +25,126 @@ pub struct Args {
     #[arg(short, long)]
     pub force: bool,
 
+    /// Another name for the note, written to its aliases front matter.
+    ///
+    /// Can be repeated to add several aliases.
+    #[arg(short, long = "alias", value_name = "ALIAS")]
+    pub aliases: Vec<String>,
+
+    /// A front matter field for the note, as key=value.
+    ///
+    /// Can be repeated to add several fields. Numbers, booleans, and lists are kept as they are,
+    /// and anything else is written as a string. Replaces a field with the same key from the config
+    /// or jot itself.
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_meta)]
+    pub meta: Vec<(String, String)>,
+
+    /// Date the note at this time instead of now, to backdate it.
+    ///
+    /// Given as YYYY-MM-DD, YYYY-MM-DD HH:MM[:SS], or an RFC 3339 timestamp. A date on its own
+    /// takes the current time of day, and times without an offset are in the note's timezone. Sets
+    /// the folder, filename, and timestamp of the note.
+    #[arg(long)]
+    pub date: Option<String>,
+
+    /// Date the note in this IANA timezone, such as Europe/Paris, instead of the configured one.
+    #[arg(long, value_name = "TZ")]
+    pub timezone: Option<String>,
+
+    /// Date the note in UTC instead of the configured timezone.
+    #[arg(long, conflicts_with = "timezone")]
+    pub utc: bool,
+
+    /// Keep the note even if it is left empty or unchanged after editing.
+    #[arg(short, long)]
+    pub keep: bool,
+
     /// Use the config file at the specified path instead of the default.
+    ///
+    /// Can also be set with the JOT_CONFIG environment variable.
     #[arg(long)]
     pub config: Option<PathBuf>,
 
+    /// Use the named notebook from the config file instead of the default notebook.
+    ///
+    /// Can also be set with the JOT_NOTEBOOK environment variable.
+    #[arg(short, long, global = true)]
+    pub notebook: Option<String>,
+
     #[command(subcommand)]
     pub subcommand: Option<Subcommand>,
 }
 
 #[derive(Debug, ClapSubcommand)]
 pub enum Subcommand {
+    /// Open an existing note in the editor.
+    ///
+    /// Notes are matched by atom ID, filename, title, or alias, and a picker is shown when more
+    /// than one matches.
+    Edit(EditCmd),
+
+    /// Move or rename a note, updating links to it in other notes.
+    ///
+    /// Rewrites wiki links and relative Markdown links that point at the note, along with the
+    /// note's own relative links if it changes directory.
+    Mv(MvCmd),
+
+    /// Move notes to the trash in the notes root, where they can be restored from.
+    Rm(RmCmd),
+
+    /// List, restore, or permanently delete the notes in the trash.
+    Trash(TrashCmd),
+
+    /// Move atoms into the archive, keeping their year and month folders.
+    Archive(ArchiveCmd),
+
+    /// List the tasks in notes, show an agenda of those that are due, or tick them off.
+    ///
+    /// Tasks are checklist items such as `- [ ] Call Sam`, optionally with a due date written as
+    /// @due(2026-10-20) or 📅 2026-10-20, and a priority written as !!!, !!, or !.
+    Tasks(TasksCmd),
+
+    /// Write a review note summarizing the atoms written or changed over a period.
+    ///
+    /// Each atom is listed with its title, date, tags, and first line, followed by the open tasks
+    /// in them. The note is written to the review directory from the configured template.
+    Review(ReviewCmd),
+
+    /// Show statistics about the notes, such as word counts, tags, links, and writing streaks.
+    Stats(StatsCmd),
+
+    /// Find notes with a query on their front matter, e.g. 'status = "draft" and "rust" in tags'.
+    ///
+    /// Compare fields with =, !=, <, <=, >, >=, in, and contains, and combine comparisons with and,
+    /// or, not, and parentheses. A field on its own matches when it is set. Unquoted dates such as
+    /// 2026-01-01 compare with timestamps by date. The path, id, title, and tags fields come from
+    /// the note itself, with tags including #tags in the text.
+    Query(QueryCmd),
+
+    /// Set, remove, or rename a front matter key across many notes.
+    ///
+    /// Only the lines for the key change, keeping the formatting of the rest of the front matter
+    /// and the body as they are.
+    Meta(MetaCmd),
+
+    /// Apply the steps in a migration script to the front matter of many notes.
+    Migrate(MigrateCmd),
+
+    /// Import notes from another note app as atoms.
+    ///
+    /// Each note is dated by when it was created in the source, and given front matter with its
+    /// timestamp and title. Links between imported notes are updated to their new paths, and
+    /// attachments are copied to an attachments folder in the atoms directory. Anything that could
+    /// not be converted is reported at the end.
+    Import(ImportCmd),
+
+    /// Export notes as an EPUB book, plain text, or JSON.
+    ///
+    /// Exports a single note from stdin, or a collection of notes chosen by path or query, in
+    /// order. EPUB books have a chapter for each note, with links between them and local images
+    /// kept working.
+    Export(ExportCmd),
+
     /// Render a note at the given path as HTML.
     ///
     /// Outputs to stdout unless the -o option is passed
@@ -41,6 +155,444 @@ pub enum Subcommand {
     /// Headless chrome must be available on the system for this to work. Requires the output
     /// option to send to a file.
     Pdf(PdfCmd),
+
+    /// Inspect and edit the configuration.
+    Config(ConfigCmd),
+
+    /// Commit changed notes to the git repository holding the notes root.
+    Commit(CommitCmd),
+
+    /// Commit any changed notes, then pull with rebase from and push to the configured remote.
+    ///
+    /// Conflicts are reported and the pull is undone, leaving local commits in place to be merged
+    /// by hand.
+    Sync(SyncCmd),
+}
+
+/// Command to open an existing note in the editor.
+#[derive(Debug, ClapArgs)]
+pub struct EditCmd {
+    /// Text to find the note by. Choose from every note if not provided.
+    pub query: Vec<String>,
+
+    /// Reopen the most recently created or modified note.
+    #[arg(short, long, conflicts_with = "query")]
+    pub last: bool,
+}
+
+/// Command to move or rename a note.
+#[derive(Debug, ClapArgs)]
+pub struct MvCmd {
+    /// The note to move.
+    ///
+    /// Relative paths are relative to the current directory inside the notes root, and to the
+    /// notes root otherwise.
+    pub from: PathBuf,
+
+    /// Where to move the note. Moving into an existing directory keeps the filename, and .md is
+    /// added if there is no extension.
+    pub to: PathBuf,
+
+    /// Show the move and a diff of every note that would change, without changing anything.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Command to move notes to the trash.
+#[derive(Debug, ClapArgs)]
+pub struct RmCmd {
+    /// The notes to remove.
+    ///
+    /// Relative paths are relative to the current directory inside the notes root, and to the
+    /// notes root otherwise.
+    #[arg(required = true)]
+    pub notes: Vec<PathBuf>,
+}
+
+/// Command to manage the trash.
+#[derive(Debug, ClapArgs)]
+pub struct TrashCmd {
+    #[command(subcommand)]
+    pub action: TrashAction,
+}
+
+#[derive(Debug, ClapSubcommand)]
+pub enum TrashAction {
+    /// List the notes in the trash, oldest first.
+    List,
+
+    /// Move a note from the trash back to where it was.
+    Restore {
+        /// The ID of the note in the trash, its original path, or part of either.
+        note: String,
+    },
+
+    /// Permanently delete every note in the trash.
+    Empty {
+        /// Confirm that the notes should be deleted.
+        #[arg(short, long)]
+        force: bool,
+    },
+}
+
+/// Command to archive atoms.
+#[derive(Debug, ClapArgs)]
+pub struct ArchiveCmd {
+    /// The atoms to archive.
+    #[arg(required_unless_present = "before")]
+    pub notes: Vec<PathBuf>,
+
+    /// Archive every atom created before the given date, e.g. 2024-01-31.
+    #[arg(long, conflicts_with = "notes")]
+    pub before: Option<NaiveDate>,
+
+    /// List the atoms that would be archived without moving them.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Command to list and complete tasks.
+#[derive(Debug, ClapArgs)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct TasksCmd {
+    #[command(subcommand)]
+    pub action: Option<TasksAction>,
+
+    #[command(flatten)]
+    pub filter: TaskFilter,
+}
+
+#[derive(Debug, ClapSubcommand)]
+pub enum TasksAction {
+    /// Show open tasks with a due date, grouped into overdue, due today, and upcoming.
+    Agenda(TaskFilter),
+
+    /// Tick off tasks in the notes they are in.
+    Done {
+        /// The IDs of the tasks, as shown when listing them, e.g. 20240101_120000:12.
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+}
+
+/// Options choosing which tasks to show.
+#[derive(Debug, ClapArgs)]
+pub struct TaskFilter {
+    /// Only show tasks with the tag, either on the task or the note it is in.
+    ///
+    /// Can be repeated to require several tags.
+    #[arg(short, long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Only show tasks in notes under this directory of the notes root.
+    #[arg(short, long)]
+    pub subdir: Option<PathBuf>,
+
+    /// Only show tasks due on or after the given date, e.g. 2024-01-31.
+    #[arg(long)]
+    pub from: Option<NaiveDate>,
+
+    /// Only show tasks due on or before the given date, e.g. 2024-01-31.
+    #[arg(long)]
+    pub to: Option<NaiveDate>,
+
+    /// Include tasks that are already done.
+    #[arg(short, long, conflicts_with = "done")]
+    pub all: bool,
+
+    /// Only show tasks that are done.
+    #[arg(short, long)]
+    pub done: bool,
+}
+
+/// Command to write a review note.
+#[derive(Debug, ClapArgs)]
+#[command(group(ArgGroup::new("period").required(true).args(["week", "month", "since"])))]
+pub struct ReviewCmd {
+    /// Review the current week, starting on Monday.
+    #[arg(short, long)]
+    pub week: bool,
+
+    /// Review the current month.
+    #[arg(short, long)]
+    pub month: bool,
+
+    /// Review everything since the given date, e.g. 2024-01-31.
+    #[arg(short, long, value_name = "DATE")]
+    pub since: Option<NaiveDate>,
+
+    /// Also render the review note to HTML next to it.
+    #[arg(long)]
+    pub html: bool,
+
+    /// Also render the review note to a PDF next to it using headless chrome.
+    #[arg(long)]
+    pub pdf: bool,
+
+    /// Overwrite the review note and any rendered files if they already exist.
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+/// Command to show statistics about the notes.
+#[derive(Debug, ClapArgs)]
+pub struct StatsCmd {
+    /// Only count notes under this directory of the notes root.
+    #[arg(short, long)]
+    pub subdir: Option<PathBuf>,
+
+    /// Only count notes created on or after the given date, e.g. 2024-01-31.
+    #[arg(long)]
+    pub from: Option<NaiveDate>,
+
+    /// Only count notes created on or before the given date, e.g. 2024-01-31.
+    #[arg(long)]
+    pub to: Option<NaiveDate>,
+
+    /// The period to count notes over for the histogram.
+    #[arg(short, long, value_enum, default_value_t = Period::Month)]
+    pub by: Period,
+
+    /// How many tags, linked notes, and long notes to show.
+    #[arg(short, long, default_value_t = 10)]
+    pub top: usize,
+
+    /// Output the statistics as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// A period to group notes by.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+/// Command to query notes by their front matter.
+#[derive(Debug, ClapArgs)]
+pub struct QueryCmd {
+    /// The query to match notes with. Every note matches if not provided.
+    pub query: Option<String>,
+
+    /// Only query notes under this directory of the notes root.
+    #[arg(short, long)]
+    pub subdir: Option<PathBuf>,
+
+    /// The fields to show for each note, separated by commas.
+    #[arg(short, long, value_delimiter = ',', default_value = "path,title")]
+    pub fields: Vec<String>,
+
+    /// The fields to sort by, separated by commas. Notes are in path order otherwise.
+    #[arg(long, value_delimiter = ',')]
+    pub sort: Vec<String>,
+
+    /// Reverse the order of the results.
+    #[arg(short, long)]
+    pub reverse: bool,
+
+    /// Show at most this many notes.
+    #[arg(short, long)]
+    pub limit: Option<usize>,
+
+    /// How to print the results. Paths prints the full path of each note on its own line, for
+    /// passing to other commands.
+    #[arg(short = 'o', long, value_enum, default_value_t = QueryFormat::Table)]
+    pub format: QueryFormat,
+}
+
+/// The ways query results can be printed.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum QueryFormat {
+    Table,
+    Json,
+    Csv,
+    Paths,
+}
+
+/// Command to edit front matter across notes.
+#[derive(Debug, ClapArgs)]
+pub struct MetaCmd {
+    #[command(subcommand)]
+    pub action: MetaAction,
+}
+
+#[derive(Debug, ClapSubcommand)]
+pub enum MetaAction {
+    /// Set a key to a value, replacing any value it already has.
+    ///
+    /// Numbers, booleans, and lists such as [a, b] are written as they are, and anything else as a
+    /// string. The placeholders {title}, {id}, {path}, {created}, and {modified} are filled in
+    /// from each note.
+    Set {
+        /// The key to set.
+        key: String,
+
+        /// The value to set it to.
+        value: String,
+
+        /// Only set the key in notes that don't have it yet.
+        #[arg(short, long)]
+        missing: bool,
+
+        #[command(flatten)]
+        selection: Selection,
+
+        #[command(flatten)]
+        write: WriteArgs,
+    },
+
+    /// Remove a key and its value.
+    Unset {
+        /// The key to remove.
+        key: String,
+
+        #[command(flatten)]
+        selection: Selection,
+
+        #[command(flatten)]
+        write: WriteArgs,
+    },
+
+    /// Rename a key, keeping its value.
+    RenameKey {
+        /// The key to rename.
+        from: String,
+
+        /// The new name for the key.
+        to: String,
+
+        #[command(flatten)]
+        selection: Selection,
+
+        #[command(flatten)]
+        write: WriteArgs,
+    },
+}
+
+/// Command to import notes from another note app.
+#[derive(Debug, ClapArgs)]
+pub struct ImportCmd {
+    /// The kind of export to import.
+    #[arg(value_enum)]
+    pub format: ImportFormat,
+
+    /// The vault or folder to import, or the file for ENEX exports.
+    pub source: PathBuf,
+}
+
+/// The kinds of export that can be imported.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ImportFormat {
+    /// An Obsidian vault.
+    Obsidian,
+
+    /// A folder of Markdown files with front matter, such as a Joplin export.
+    #[value(alias = "joplin")]
+    Markdown,
+
+    /// An Evernote ENEX file.
+    Enex,
+
+    /// A folder of plain .txt and .md files.
+    Plain,
+}
+
+/// Command to export notes to other formats.
+#[derive(Debug, ClapArgs)]
+pub struct ExportCmd {
+    /// The format to export to.
+    #[arg(short, long, value_enum)]
+    pub format: ExportFormat,
+
+    /// The notes to export, in order.
+    ///
+    /// Relative paths are relative to the current directory inside the notes root, and to the
+    /// notes root otherwise. Reads a single note from stdin if no notes, query, --all, or --paths
+    /// are given.
+    pub notes: Vec<PathBuf>,
+
+    /// Export the notes matching a query, as used by jot query.
+    #[arg(short, long, conflicts_with = "notes")]
+    pub query: Option<String>,
+
+    /// Export every note.
+    #[arg(long, conflicts_with_all = ["notes", "query"])]
+    pub all: bool,
+
+    /// Export the notes listed on stdin in order, one path per line, such as the output of
+    /// jot query --format paths.
+    #[arg(long, conflicts_with_all = ["notes", "query", "all"])]
+    pub paths: bool,
+
+    /// Write to a file at the given path, which must not exist, instead of stdout.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// The title of an EPUB book, which defaults to the title of its first note.
+    #[arg(short, long)]
+    pub title: Option<String>,
+
+    #[command(flatten)]
+    pub style: StyleArgs,
+}
+
+/// The formats notes can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// An EPUB book with a chapter for each note, styled with the theme and stylesheets.
+    Epub,
+
+    /// Plain text with the Markdown syntax stripped.
+    Txt,
+
+    /// JSON with the path, title, front matter, HTML, and Markdown syntax tree of each note.
+    Json,
+}
+
+/// Command to run a migration script over notes.
+#[derive(Debug, ClapArgs)]
+pub struct MigrateCmd {
+    /// The TOML migration script, with a [[step]] table for each change to make in order.
+    pub script: PathBuf,
+
+    #[command(flatten)]
+    pub selection: Selection,
+
+    #[command(flatten)]
+    pub write: WriteArgs,
+}
+
+/// Options choosing the notes to change.
+#[derive(Debug, ClapArgs)]
+#[group(required = true)]
+pub struct Selection {
+    /// The notes to change.
+    ///
+    /// Relative paths are relative to the current directory inside the notes root, and to the
+    /// notes root otherwise.
+    pub notes: Vec<PathBuf>,
+
+    /// Change the notes matching a query, as used by jot query.
+    #[arg(short, long, conflicts_with = "notes")]
+    pub query: Option<String>,
+
+    /// Change every note.
+    #[arg(long, conflicts_with_all = ["notes", "query"])]
+    pub all: bool,
+}
+
+/// Options for writing changes to many notes.
+#[derive(Debug, ClapArgs)]
+pub struct WriteArgs {
+    /// Show a diff of every note that would change, without changing anything.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Skip backing up the notes before changing them.
+    #[arg(long)]
+    pub no_backup: bool,
 }
 
 /// Command to render a note as HTML from the give path.
@@ -52,6 +604,11 @@ pub struct HtmlCmd {
     #[arg(short, long)]
     pub input: Option<PathBuf>,
 
+    /// Render each note listed on stdin, one path per line, such as the output of jot query
+    /// --format paths. Each note is written to its own file, so --output is required.
+    #[arg(long, conflicts_with = "input", requires = "output")]
+    pub paths: bool,
+
     /// Output to a file at the given path.
     ///
     /// If not provided, will output to stdout.
@@ -70,6 +627,9 @@ pub struct HtmlCmd {
     /// This is useful for producing snippets.
     #[arg(short, long)]
     pub raw: bool,
+
+    #[command(flatten)]
+    pub style: StyleArgs,
 }
 
 #[derive(Debug, ClapArgs)]
@@ -80,6 +640,11 @@ pub struct PdfCmd {
     #[arg(short, long)]
     pub input: Option<PathBuf>,
 
+    /// Render each note listed on stdin, one path per line, such as the output of jot query
+    /// --format paths. Each note is written to its own file, so --output is required.
+    #[arg(long, conflicts_with = "input", requires = "output")]
+    pub paths: bool,
+
     /// Output the file to the given path.
     ///
     /// If not provided, will output to stdout. This should only be used to redirect to a file.
@@ -92,4 +657,114 @@ pub struct PdfCmd {
     ///  - A full filename will save to the file, but will not create any directories or overwrite existing files.
     #[arg(short, long)]
     pub output: Option<Option<PathBuf>>,
+
+    #[command(flatten)]
+    pub style: StyleArgs,
+}
+
+/// Options choosing the stylesheets to inject when rendering HTML or PDFs.
+///
+/// Stylesheets are injected in order over the theme: those from the config (or a discovered
+/// jot.css), print stylesheets for PDFs, those listed in the note's css front matter, then those
+/// passed here.
+#[derive(Debug, ClapArgs)]
+pub struct StyleArgs {
+    /// The built-in theme to render with, overriding the config.
+    #[arg(long, value_enum)]
+    pub theme: Option<Theme>,
+
+    /// Add a stylesheet after any from the config or the note's front matter.
+    ///
+    /// Can be repeated to add multiple stylesheets, which are injected in order.
+    #[arg(long = "css", value_name = "PATH")]
+    pub css: Vec<PathBuf>,
+
+    /// Skip the theme and stylesheets from the config and the note's front matter.
+    ///
+    /// Stylesheets passed with --css and a theme passed with --theme are still used.
+    #[arg(long)]
+    pub no_css: bool,
+}
+
+/// Command to inspect and edit the configuration.
+#[derive(Debug, ClapArgs)]
+pub struct ConfigCmd {
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+#[derive(Debug, ClapSubcommand)]
+pub enum ConfigAction {
+    /// Show the fully resolved configuration along with where each value came from.
+    Show,
+
+    /// Print the resolved value of a single setting.
+    Get {
+        /// The setting to print, e.g. root.
+        key: String,
+    },
+
+    /// Set a value in the config file, preserving its comments and formatting.
+    ///
+    /// Values are parsed as TOML where possible, so true and false set booleans, and anything else
+    /// is treated as a string. Notebook settings use dotted keys, e.g. notebooks.work.root.
+    Set {
+        /// The setting to change.
+        key: String,
+
+        /// The new value.
+        value: String,
+
+        /// Edit the nearest .jot.toml instead of the global config, creating one in the current
+        /// directory if none exists.
+        #[arg(short, long)]
+        local: bool,
+    },
+
+    /// Print the paths of the global config file and any local .jot.toml in use.
+    Path,
+
+    /// Write a commented default config file.
+    Init {
+        /// Write a .jot.toml in the current directory instead of the global config.
+        #[arg(short, long)]
+        local: bool,
+
+        /// Overwrite the file if it already exists.
+        #[arg(short, long)]
+        force: bool,
+    },
+}
+
+/// Command to commit notes to git.
+#[derive(Debug, ClapArgs)]
+pub struct CommitCmd {
+    /// The notes to commit. Commits every change under the notes root if none are given.
+    pub notes: Vec<PathBuf>,
+
+    /// The commit message, otherwise taken from the note's title or first line.
+    #[arg(short, long)]
+    pub message: Option<String>,
+}
+
+/// Command to sync the notes with the git remote.
+#[derive(Debug, ClapArgs)]
+pub struct SyncCmd {
+    /// The message for committing any changed notes before syncing.
+    #[arg(short, long)]
+    pub message: Option<String>,
+
+    /// Pull without pushing afterwards.
+    #[arg(long)]
+    pub no_push: bool,
+}
+
+/// Split a `key=value` argument at the first equals sign.
+fn parse_meta(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected key=value, got '{}'", arg)),
+    }
 }
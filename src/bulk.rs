@@ -0,0 +1,149 @@
+//! Changing many notes at once, shared by the commands that edit front matter in bulk.
+//!
+//! Changes are worked out for every note before any are written, so that a problem with one note
+//! leaves them all alone. They can be shown as a diff instead, and otherwise the notes are backed
+//! up to a hidden `.backup` folder in the notes root first, in a folder for each run.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use similar::TextDiff;
+
+use crate::{
+    args::{Selection, WriteArgs},
+    config::{Create, Timezone},
+    note,
+    path::{create_unique_dir, locate_note},
+    query::{Expr, Fields},
+};
+
+const BACKUP_DIR: &str = ".backup";
+
+/// The notes chosen on the command line, which must be in the canonical notes root. Queries read
+/// and date timestamps as new notes are written.
+pub fn select(selection: &Selection, root: &Path, create: &Create) -> Result<Vec<PathBuf>> {
+    if !selection.notes.is_empty() {
+        return selection
+            .notes
+            .iter()
+            .map(|path| locate_note(path, root))
+            .collect();
+    }
+
+    let expr = selection.query.as_deref().map(Expr::parse).transpose()?;
+    let mut notes = Vec::new();
+    for path in note::walk(root)? {
+        let matches = match &expr {
+            Some(expr) => {
+                let md = fs::read_to_string(&path)
+                    .with_context(|| format!("Unable to read note {}", path.to_string_lossy()))?;
+                expr.matches(&Fields::new(&md, &path, root, create))
+            }
+            None => true,
+        };
+        if matches {
+            notes.push(path);
+        }
+    }
+
+    Ok(notes)
+}
+
+/// Fill in the `{title}`, `{id}`, `{path}`, `{created}`, and `{modified}` placeholders in a value
+/// from the note, with the dates in the timezone new notes are dated in.
+pub fn expand(template: &str, md: &str, path: &Path, root: &Path, create: &Create) -> String {
+    let date =
+        |date: Option<chrono::NaiveDate>| date.map(|date| date.to_string()).unwrap_or_default();
+    template
+        .replace("{title}", &note::title(md, Some(path)).unwrap_or_default())
+        .replace(
+            "{id}",
+            &path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        )
+        .replace(
+            "{path}",
+            &path.strip_prefix(root).unwrap_or(path).to_string_lossy(),
+        )
+        .replace("{created}", &date(note::created(md, path, create)))
+        .replace("{modified}", &date(note::modified(path, create.timezone)))
+}
+
+/// Whether the template has any placeholders for [`expand`] to fill in.
+pub fn has_placeholders(template: &str) -> bool {
+    ["{title}", "{id}", "{path}", "{created}", "{modified}"]
+        .iter()
+        .any(|placeholder| template.contains(placeholder))
+}
+
+/// Write the notes that changed, given as their path, original content, and new content, backing
+/// them up to a folder named for the time in the timezone. With --dry-run a diff is printed
+/// instead.
+pub fn write(
+    root: &Path,
+    changes: &[(PathBuf, String, String)],
+    args: &WriteArgs,
+    timezone: Timezone,
+) -> Result<()> {
+    let display = |path: &Path| {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned()
+    };
+    let changed = changes
+        .iter()
+        .filter(|(_, before, after)| before != after)
+        .collect::<Vec<_>>();
+    let count = |count: usize| match count {
+        1 => "1 note".to_string(),
+        count => format!("{} notes", count),
+    };
+
+    if args.dry_run {
+        for (path, before, after) in &changed {
+            print!(
+                "{}",
+                TextDiff::from_lines(before, after)
+                    .unified_diff()
+                    .header(&display(path), &display(path))
+            );
+        }
+        println!("{} would change", count(changed.len()));
+        return Ok(());
+    }
+
+    // Each run gets its own backup folder, even when two start in the same second
+    let mut backup = root
+        .join(BACKUP_DIR)
+        .join(timezone.now().format("%Y%m%d_%H%M%S").to_string());
+    if !args.no_backup && !changed.is_empty() {
+        backup = create_unique_dir(&backup)?;
+        for (path, before, _) in &changed {
+            let copy = backup.join(path.strip_prefix(root).unwrap_or(path));
+            if let Some(parent) = copy.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&copy, before)
+                .with_context(|| format!("Unable to back up {}", path.to_string_lossy()))?;
+        }
+    }
+
+    for (path, _, after) in &changed {
+        fs::write(path, after)
+            .with_context(|| format!("Unable to update note {}", path.to_string_lossy()))?;
+        println!("Updated {}", display(path));
+    }
+    match (changed.len(), args.no_backup) {
+        (0, _) => println!("No notes changed"),
+        (n, false) => println!("Backed up {} to {}", count(n), display(&backup)),
+        (n, true) => println!("Updated {}", count(n)),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    args::ArchiveCmd,
+    config::Config,
+    links::{self, Index},
+    note,
+    path::locate_note,
+};
+
+/// Command called to move atoms into the archive.
+///
+/// Atoms keep their path below the atoms subdirectory, so the year and month folders are the same
+/// in the archive. Warns about links from other notes that will no longer work.
+pub fn archive(args: &ArchiveCmd, config: &Config) -> Result<()> {
+    let root = config
+        .root()
+        .canonicalize()
+        .with_context(|| format!("Notes root {} not found", config.root().to_string_lossy()))?;
+    let atoms = config.base_dir();
+    let Ok(atoms) = atoms.canonicalize() else {
+        bail!("Atoms directory {} not found", atoms.to_string_lossy());
+    };
+    // The archive is only created once something is archived, so when it isn't there yet it is
+    // found from the canonical root instead
+    let archive = config.archive_dir();
+    let archive = archive
+        .canonicalize()
+        .unwrap_or_else(|_| root.join(archive.strip_prefix(config.root()).unwrap_or(&archive)));
+
+    let notes = match args.before {
+        Some(before) => note::walk(&atoms)?
+            .into_iter()
+            .filter(|note| note::id_date(note).is_some_and(|date| date < before))
+            .collect(),
+        None => args
+            .notes
+            .iter()
+            .map(|path| locate_note(path, &root))
+            .collect::<Result<Vec<_>>>()?,
+    };
+
+    // Work out every destination up front so that nothing moves if any of them are a problem
+    let moves = notes
+        .into_iter()
+        .map(|note| {
+            let Ok(path) = note.strip_prefix(&atoms) else {
+                bail!("{} is not an atom", note.to_string_lossy());
+            };
+            let dest = archive.join(path);
+            if dest.exists() {
+                bail!("{} is already archived", dest.to_string_lossy());
+            }
+            Ok((note.clone(), dest))
+        })
+        .collect::<Result<Vec<(PathBuf, PathBuf)>>>()?;
+
+    let display = |path: &PathBuf| {
+        path.strip_prefix(&root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    if args.dry_run {
+        for (note, dest) in &moves {
+            println!("Archive {} to {}", display(note), display(dest));
+        }
+        return Ok(());
+    }
+
+    let index = Index::new(&root, note::walk(&root)?);
+    for (note, dest) in &moves {
+        links::warn_incoming(&index, note);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(note, dest)
+            .with_context(|| format!("Unable to archive {}", note.to_string_lossy()))?;
+        println!("Archived {} to {}", display(note), display(dest));
+    }
+    if moves.is_empty() {
+        println!("No atoms to archive");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use anyhow::Result;
+
+use crate::{
+    args::CommitCmd,
+    config::Config,
+    git::{self, Repo},
+};
+
+/// Command called to commit notes to git.
+///
+/// Commits the given notes, or every change under the notes root when none are given. The message
+/// is taken from the note when only one has changed.
+pub fn commit(args: &CommitCmd, config: &Config) -> Result<()> {
+    let repo = Repo::open(config)?;
+    let changed = match args.notes.is_empty() {
+        true => repo.changed()?,
+        false => args.notes.clone(),
+    };
+
+    let message = match &args.message {
+        Some(message) => message.clone(),
+        None => git::message(&changed)?,
+    };
+    match repo.commit(&args.notes, &message)? {
+        true => println!("Committed: {}", message),
+        false => println!("Nothing to commit"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use toml_edit::{DocumentMut, Item, Table, TableLike};
+
+use crate::{
+    args::{Args, ConfigAction, ConfigCmd},
+    config::{Config, KEYS},
+};
+
+/// Command called to inspect and edit the configuration.
+///
+/// Only showing and getting values requires a valid config, so the config is built here rather
+/// than up front, leaving the other actions available to fix a broken config file.
+pub fn configure(cmd: &ConfigCmd, args: &Args) -> Result<()> {
+    match &cmd.action {
+        ConfigAction::Show => show(&Config::try_new(args)?),
+        ConfigAction::Get { key } => {
+            if !KEYS.contains(&key.as_str()) {
+                bail!(
+                    "Unknown setting '{}', expected one of {}",
+                    key,
+                    KEYS.join(", ")
+                );
+            }
+            match Config::try_new(args)?.get(key) {
+                // Print strings raw so the output can be used directly in scripts
+                Some((toml::Value::String(s), _)) => println!("{}", s),
+                Some((value, _)) => println!("{}", value),
+                None => {}
+            }
+            Ok(())
+        }
+        ConfigAction::Set { key, value, local } => {
+            let path = match local {
+                true => Config::local_path().map_or_else(Config::new_local_path, Ok)?,
+                false => Config::global_path(args)?,
+            };
+            set(&path, key, value)?;
+            println!("Set {} in {}", key, path.to_string_lossy());
+            Ok(())
+        }
+        ConfigAction::Path => {
+            let global = Config::global_path(args)?;
+            let missing = if global.is_file() { "" } else { " (not found)" };
+            println!("{}{}", global.to_string_lossy(), missing);
+            if let Some(local) = Config::local_path() {
+                println!("{}", local.to_string_lossy());
+            }
+            Ok(())
+        }
+        ConfigAction::Init { local, force } => {
+            let path = match local {
+                true => Config::new_local_path()?,
+                false => Config::global_path(args)?,
+            };
+            if path.exists() && !force {
+                bail!(
+                    "Config file {} already exists, use --force to overwrite it",
+                    path.to_string_lossy()
+                );
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, Config::template())?;
+            println!("{}", path.to_string_lossy());
+            Ok(())
+        }
+    }
+}
+
+/// Print each resolved setting as TOML, with its source as a trailing comment.
+fn show(config: &Config) -> Result<()> {
+    let lines = config
+        .entries()
+        .into_iter()
+        .map(|(key, value, source)| (format!("{} = {}", key, value), source))
+        .collect::<Vec<_>>();
+    let width = lines.iter().map(|(line, _)| line.len()).max().unwrap_or(0);
+
+    for (line, source) in lines {
+        println!("{:width$}  # {}", line, source, width = width);
+    }
+
+    Ok(())
+}
+
+/// Set a dotted key in the TOML file at the given path, creating the file if required.
+///
+/// Edits the document in place so that comments, ordering, and formatting are preserved.
+fn set(path: &Path, key: &str, value: &str) -> Result<()> {
+    let segments = key.split('.').collect::<Vec<_>>();
+
+    let content = match path.is_file() {
+        true => fs::read_to_string(path)?,
+        false => String::new(),
+    };
+    let mut doc = content
+        .parse::<DocumentMut>()
+        .with_context(|| format!("Unable to parse {}", path.to_string_lossy()))?;
+
+    // Anything that isn't valid as a TOML value is taken to be a bare string
+    let mut new_value = value
+        .parse::<toml_edit::Value>()
+        .unwrap_or_else(|_| value.into());
+
+    let (last, tables) = segments.split_last().expect("Key has at least one segment");
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+    for segment in tables {
+        if !table.contains_key(segment) {
+            // Implicit so there is no empty [notebooks] header above the notebook tables
+            let mut new_table = Table::new();
+            new_table.set_implicit(true);
+            table.insert(segment, Item::Table(new_table));
+        }
+        table = table
+            .get_mut(segment)
+            .and_then(Item::as_table_like_mut)
+            .with_context(|| format!("'{}' in {} is not a table", segment, key))?;
+    }
+
+    // Replace existing values in place to keep the comments attached to the key and value
+    match table.get_mut(last).and_then(Item::as_value_mut) {
+        Some(old) => {
+            *new_value.decor_mut() = old.decor().clone();
+            *old = new_value;
+        }
+        None => {
+            table.insert(last, Item::Value(new_value));
+        }
+    }
+
+    // Check the edited file against the config schema before writing anything
+    let content = doc.to_string();
+    Config::validate(&content, path)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+
+    Ok(())
+}
@@ -1,9 +1,22 @@
-use std::{fs, process::Command};
+use std::{
+    env,
+    fmt::Write as _,
+    fs,
+    io::{self, Write},
+    path::Path,
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
 use chrono::prelude::*;
 
-use crate::{args::Args, config::Config};
+use crate::{
+    args::Args,
+    config::{Config, Timezone},
+    editor::Editor,
+    frontmatter, git,
+    hooks::{self, Hook},
+    path::create_unique,
+};
 
 pub fn create(args: &Args, config: &Config) -> Result<()> {
     // Abort early if attempting to create an empty note without editing
@@ -14,19 +27,41 @@ pub fn create(args: &Args, config: &Config) -> Result<()> {
     }
 
     // Get the date, including the year and month for building the path and for the front matter
-    let date = Local::now();
+    let timezone = config.create.timezone;
+    let date = match &args.date {
+        Some(date) => parse_date(date, timezone)?,
+        None => timezone.now(),
+    };
+    let timestamp = config.create.timestamp(&date)?;
+
+    // Then process the provided text, adding a heading (#) to the front if required. The first
+    // line is also the note's title, which goes in the front matter along with any aliases
+    let body_text = args.text.as_deref().unwrap_or_default();
+    let body_text = body_text.trim();
+    let title = body_text
+        .lines()
+        .next()
+        .map(|line| line.trim_start_matches('#').trim())
+        .filter(|title| !title.is_empty());
 
-    // Then process the provided text, adding a heading (#) to the front if required, then push
-    // onto the text
     let mut text = format!(
-        "---\ntimestamp: {}\ngenerated-by: {} {}\n---\n\n",
-        date.format("%FT%T%:z"),
+        "---\ntimestamp: {}\ngenerated-by: {} {}\n",
+        frontmatter::scalar(&timestamp)?,
         env!("CARGO_PKG_NAME"),
         env!("CARGO_PKG_VERSION")
     );
-    let body_text = args.text.as_ref().map(String::as_str).unwrap_or_default();
-    let body_text = body_text.trim();
-    let heading_leader = if body_text.starts_with("#") || body_text.len() == 0 {
+    if let Some(title) = title {
+        text.push_str(&format!("title: {}\n", frontmatter::scalar(title)?));
+    }
+    if !args.aliases.is_empty() {
+        text.push_str("aliases:\n");
+        for alias in &args.aliases {
+            text.push_str(&format!("  - {}\n", frontmatter::scalar(alias)?));
+        }
+    }
+    text.push_str("---\n\n");
+
+    let heading_leader = if body_text.starts_with("#") || body_text.is_empty() {
         ""
     } else {
         "# "
@@ -35,35 +70,198 @@ pub fn create(args: &Args, config: &Config) -> Result<()> {
     text.push_str(body_text);
     text.push('\n');
 
-    // For the location to save, start with the base notes folder to add to
-    let mut note_path = config.base_dir();
+    // Fields from the config and --meta go after the built-in ones, replacing any with the same key
+    for (key, value) in fields(args, config, &date)? {
+        text = frontmatter::set(&text, &key, &value);
+    }
+
+    // Ensure all the folders are created for the note's location in the base notes folder
+    let note_path = config.atom_path(&date);
+    if let Some(dir) = note_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
 
-    // Ensure all the folders are created
-    note_path.push(date.format("%Y/%m").to_string());
-    fs::create_dir_all(&note_path)?;
+    // Now write out the file, exclusively creating it so that two jots started in the same second
+    // can never clobber one another - the later one gets a suffixed filename instead
+    let (mut file, final_path) = create_unique(&note_path)?;
+    file.write_all(text.as_bytes())?;
+    drop(file);
 
-    // Now write out the file
-    let filename = format!("{}.md", date.format("%Y%m%d_%H%M%S"));
-    note_path.push(&filename);
-    fs::write(&note_path, &text)?;
+    if final_path != note_path && !args.no_edit {
+        eprintln!(
+            "{} already exists, created {} instead",
+            note_path.to_string_lossy(),
+            final_path.to_string_lossy()
+        );
+    }
+    let note_path = final_path;
+    hooks::run(config, Hook::PostCreate, Some(&note_path), None, &text)?;
 
     // Editing behavior:
     // - Require an editor to be identified in config (first) or path
     // - File is created whether editor can be opened or not
     // - Attempt to open the file for editing as long as no-edit is not set
     // - Otherwise print the full path of the created file
+    // - Once the editor exits, remove the note if it is empty or exactly as jot wrote it,
+    //   otherwise run the post edit hook and commit it as long as the note is still there
     if args.no_edit {
+        git::auto_commit(config, &note_path)?;
         println!("{}", note_path.to_string_lossy());
     } else {
-        let mut cmd = Command::new(&config.editor);
-        cmd.arg(&note_path);
+        Editor::new(config)?.open(&note_path)?;
 
-        if config.jump {
-            cmd.arg(format!("+"));
+        let discarded = if config.discard_empty && !args.keep {
+            discard_reason(&note_path, &text)?
+        } else {
+            None
+        };
+        if let Some(reason) = discarded {
+            fs::remove_file(&note_path)?;
+            eprintln!(
+                "Discarded {} note {}, use --keep to keep it",
+                reason,
+                note_path.to_string_lossy()
+            );
+        } else if let Ok(md) = fs::read_to_string(&note_path) {
+            hooks::run(config, Hook::PostEdit, Some(&note_path), None, &md)?;
+            git::auto_commit(config, &note_path)?;
         }
-
-        cmd.spawn()?.wait()?;
     }
 
     Ok(())
 }
+
+/// The extra front matter fields for a new note as YAML, with those from the config first and then
+/// those given with --meta.
+fn fields(
+    args: &Args,
+    config: &Config,
+    date: &DateTime<FixedOffset>,
+) -> Result<Vec<(String, String)>> {
+    let mut fields = Vec::new();
+    for (key, value) in &config.create.frontmatter {
+        let value = yaml(value, date)
+            .with_context(|| format!("Invalid front matter field {} in the config", key))?;
+        fields.push((key.clone(), value));
+    }
+    for (key, value) in &args.meta {
+        fields.push((key.clone(), frontmatter::value(value)?));
+    }
+
+    // Keys are written as they are, so they can't be anything YAML would need to quote
+    for (key, _) in &fields {
+        if key.contains(':') || frontmatter::scalar(key)? != *key {
+            bail!("Invalid front matter key '{}'", key);
+        }
+    }
+
+    Ok(fields)
+}
+
+/// Write a config value as YAML, filling in the placeholders in any text. Lists and tables are
+/// written in flow style so that the value fits on the key's line.
+fn yaml(value: &toml::Value, date: &DateTime<FixedOffset>) -> Result<String> {
+    let flow = |value: &toml::Value| match value {
+        toml::Value::String(text) => Ok(serde_json::to_string(&expand(text, date)?)?),
+        value => yaml(value, date),
+    };
+
+    Ok(match value {
+        toml::Value::String(text) => frontmatter::scalar(&expand(text, date)?)?,
+        toml::Value::Array(items) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(flow)
+                .collect::<Result<Vec<_>>>()?
+                .join(", ")
+        ),
+        toml::Value::Table(table) => format!(
+            "{{{}}}",
+            table
+                .iter()
+                .map(|(key, value)| Ok(format!(
+                    "{}: {}",
+                    serde_json::to_string(key)?,
+                    flow(value)?
+                )))
+                .collect::<Result<Vec<_>>>()?
+                .join(", ")
+        ),
+        value => value.to_string(),
+    })
+}
+
+/// Fill in strftime codes with the note's date, then `${VAR}` with environment variables, which
+/// are left empty when they aren't set.
+fn expand(text: &str, date: &DateTime<FixedOffset>) -> Result<String> {
+    let mut formatted = String::new();
+    write!(formatted, "{}", date.format(text))
+        .map_err(|_| anyhow!("Invalid date format in '{}'", text))?;
+
+    let mut expanded = String::new();
+    let mut rest = formatted.as_str();
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&env::var(&rest[start + 2..end]).unwrap_or_default());
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+/// Read the date given with --date in the timezone. A date without a time takes the current time
+/// of day.
+fn parse_date(date: &str, timezone: Timezone) -> Result<DateTime<FixedOffset>> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+        return Ok(timezone.convert(date));
+    }
+
+    let local = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(date, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .ok()
+            .map(|day| day.and_time(timezone.now().time()))
+    })
+    .with_context(|| {
+        format!(
+            "Invalid date '{}', expected YYYY-MM-DD, YYYY-MM-DD HH:MM, or an RFC 3339 timestamp",
+            date
+        )
+    })?;
+
+    timezone
+        .at(&local)
+        .with_context(|| format!("{} doesn't exist in the {} timezone", local, timezone))
+}
+
+/// Why the note can be discarded: its body is empty, or it is unchanged from what jot wrote.
+///
+/// A note that no longer exists, for example when it was moved from inside the editor, is left
+/// alone.
+fn discard_reason(path: &Path, original: &str) -> Result<Option<&'static str>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    Ok(if frontmatter::split(&content).1.trim().is_empty() {
+        Some("empty")
+    } else if content == original {
+        Some("unchanged")
+    } else {
+        None
+    })
+}
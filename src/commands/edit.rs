@@ -0,0 +1,136 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    args::EditCmd,
+    config::Config,
+    editor::Editor,
+    git,
+    hooks::{self, Hook},
+    note::{self, Note},
+    picker,
+};
+
+/// Command called to open an existing note in the editor.
+///
+/// The note is found by a query matched against note IDs, paths, titles, and aliases, with a
+/// picker to choose between several matches. With --last the latest note is reopened instead.
+pub fn edit(args: &EditCmd, config: &Config) -> Result<()> {
+    let path = match args.last {
+        true => note::latest(config.root())?.context("There are no notes to edit")?,
+        false => match find(&args.query.join(" "), config.root())? {
+            Some(path) => path,
+            None => return Ok(()),
+        },
+    };
+
+    Editor::new(config)?.open(&path)?;
+
+    // The note may have been moved or deleted from inside the editor
+    if let Ok(md) = fs::read_to_string(&path) {
+        hooks::run(config, Hook::PostEdit, Some(&path), None, &md)?;
+        git::auto_commit(config, &path)?;
+    }
+
+    Ok(())
+}
+
+/// Find the note matching the query, asking the user to choose if there are several. Returns None
+/// if the user cancels.
+///
+/// Exact matches on an ID, filename, title, or alias win, followed by notes containing the query,
+/// and only then fuzzy matches. An empty query offers every note.
+fn find(query: &str, root: &Path) -> Result<Option<PathBuf>> {
+    let query = query.trim();
+    let mut notes = note::walk(root)?
+        .iter()
+        .map(|path| Note::read(path))
+        .collect::<Result<Vec<_>>>()?;
+    // Newest atoms first
+    notes.reverse();
+
+    let label = |note: &Note| {
+        let path = note.path.strip_prefix(root).unwrap_or(&note.path);
+        match note.title == note.id {
+            true => path.to_string_lossy().into_owned(),
+            false => format!("{}  {}", path.to_string_lossy(), note.title),
+        }
+    };
+
+    let candidates = match query.is_empty() {
+        true => notes.iter().collect(),
+        false => matching(query, &notes, label),
+    };
+
+    match candidates.as_slice() {
+        [] => bail!("No notes match '{}'", query),
+        [note] => Ok(Some(note.path.clone())),
+        _ if picker::available() => {
+            let labels = candidates
+                .iter()
+                .map(|note| label(note))
+                .collect::<Vec<_>>();
+            Ok(picker::pick(&labels)?.map(|i| candidates[i].path.clone()))
+        }
+        _ => bail!(
+            "{} notes match '{}', use a more specific query:\n{}",
+            candidates.len(),
+            query,
+            candidates
+                .iter()
+                .map(|note| format!("  {}", note.path.to_string_lossy()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    }
+}
+
+/// The notes matching the query, taking the best kind of match available.
+fn matching<'a>(query: &str, notes: &'a [Note], label: impl Fn(&Note) -> String) -> Vec<&'a Note> {
+    let lower = query.to_lowercase();
+    let names = |note: &'a Note| {
+        let filename = note
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        [note.id.clone(), filename, note.title.clone()]
+            .into_iter()
+            .chain(note.aliases.iter().cloned())
+            .map(|name| name.to_lowercase())
+            .collect::<Vec<_>>()
+    };
+
+    let exact = notes
+        .iter()
+        .filter(|note| names(note).contains(&lower))
+        .collect::<Vec<_>>();
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    let containing = notes
+        .iter()
+        .filter(|note| {
+            label(note).to_lowercase().contains(&lower)
+                || names(note).iter().any(|name| name.contains(&lower))
+        })
+        .collect::<Vec<_>>();
+    if !containing.is_empty() {
+        return containing;
+    }
+
+    let mut fuzzy = notes
+        .iter()
+        .filter_map(|note| {
+            let text = format!("{} {}", label(note), note.aliases.join(" "));
+            picker::score(query, &text).map(|score| (score, note))
+        })
+        .collect::<Vec<_>>();
+    fuzzy.sort_by_key(|(score, _)| -score);
+    fuzzy.into_iter().map(|(_, note)| note).collect()
+}
@@ -0,0 +1,186 @@
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::{self, Cursor, IsTerminal, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    args::{ExportCmd, ExportFormat, Selection},
+    bulk,
+    config::Config,
+    export::{self, Epub},
+    html,
+    links::{self, Index, Kind},
+    note,
+    path::{locate_note, read_md_from_stdin, read_paths_from_stdin},
+};
+
+/// The title of an EPUB book when neither it nor its first note have one.
+const DEFAULT_TITLE: &str = "Jot Notes";
+
+/// Command called to export notes as an EPUB book, plain text, or JSON.
+///
+/// Exports the chosen notes in order, or a single note from stdin when none are chosen, to stdout
+/// or a new file. The notes can also be listed on stdin, so that the results of a query can be
+/// exported in the order it sorted them.
+pub fn export(cmd: &ExportCmd, config: &Config) -> Result<()> {
+    let root = config
+        .root()
+        .canonicalize()
+        .with_context(|| format!("Notes root {} not found", config.root().to_string_lossy()))?;
+
+    // Check the output before reading any notes in case there are issues
+    match &cmd.output {
+        Some(output) if output.exists() => {
+            bail!("{} already exists", output.to_string_lossy())
+        }
+        None if cmd.format == ExportFormat::Epub && io::stdout().is_terminal() => {
+            bail!("EPUB books must be written to a file with --output or redirected")
+        }
+        _ => {}
+    }
+
+    let stdin = cmd.notes.is_empty() && cmd.query.is_none() && !cmd.all && !cmd.paths;
+    let notes = match stdin {
+        true => vec![(None, read_md_from_stdin()?)],
+        false => {
+            let paths = match cmd.paths {
+                true => read_paths_from_stdin()?
+                    .iter()
+                    .map(|path| locate_note(path, &root))
+                    .collect::<Result<Vec<_>>>()?,
+                false => {
+                    let selection = Selection {
+                        notes: cmd.notes.clone(),
+                        query: cmd.query.clone(),
+                        all: cmd.all,
+                    };
+                    bulk::select(&selection, &root, &config.create)?
+                }
+            };
+            paths
+                .into_iter()
+                .map(|path| {
+                    let md = fs::read_to_string(&path).with_context(|| {
+                        format!("Unable to read note {}", path.to_string_lossy())
+                    })?;
+                    Ok((Some(path), md))
+                })
+                .collect::<Result<Vec<_>>>()?
+        }
+    };
+    if notes.is_empty() {
+        bail!("No notes to export");
+    }
+
+    let output = match cmd.format {
+        ExportFormat::Txt => notes
+            .iter()
+            .map(|(_, md)| export::text(md))
+            .collect::<Result<Vec<_>>>()?
+            .join("\n")
+            .into_bytes(),
+        ExportFormat::Json => {
+            let mut json = notes
+                .iter()
+                .map(|(path, md)| export::json(md, path.as_deref(), &root))
+                .collect::<Result<Vec<_>>>()?;
+
+            // A single note is exported as an object, and a collection as an array even when
+            // only one note is in it
+            let single = cmd.query.is_none() && !cmd.all && !cmd.paths && json.len() == 1;
+            let json = match single {
+                true => json.remove(0),
+                false => json.into(),
+            };
+            (serde_json::to_string_pretty(&json)? + "\n").into_bytes()
+        }
+        ExportFormat::Epub => epub(cmd, config, &root, &notes)?,
+    };
+
+    match &cmd.output {
+        Some(path) => OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(path)
+            .with_context(|| format!("Unable to create {}", path.to_string_lossy()))?
+            .write_all(&output)?,
+        None => io::stdout().write_all(&output)?,
+    }
+
+    Ok(())
+}
+
+/// Build an EPUB book with a chapter for each note, styled like `jot html` output.
+///
+/// Links between the notes go to their chapters, and local images are included in the book.
+fn epub(
+    cmd: &ExportCmd,
+    config: &Config,
+    root: &Path,
+    notes: &[(Option<PathBuf>, String)],
+) -> Result<Vec<u8>> {
+    let title = cmd
+        .title
+        .clone()
+        .or_else(|| note::title(&notes[0].1, notes[0].0.as_deref()))
+        .unwrap_or(DEFAULT_TITLE.to_string());
+
+    // The note's own stylesheets aren't used, as the whole book shares one
+    let mut css = html::theme(config, &cmd.style)
+        .css()
+        .map(|css| css.concat())
+        .unwrap_or_default();
+    for path in html::stylesheets(config, &cmd.style, "", None, false)? {
+        css.push_str(
+            &fs::read_to_string(&path)
+                .with_context(|| format!("Unable to read stylesheet {}", path.to_string_lossy()))?,
+        );
+    }
+
+    let paths = notes
+        .iter()
+        .filter_map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    let chapters = paths
+        .iter()
+        .enumerate()
+        .map(|(i, path)| (path.clone(), Epub::href(i)))
+        .collect::<HashMap<_, _>>();
+    let index = Index::new(root, paths);
+
+    let mut book = Epub::new(title, css);
+    for (i, (path, md)) in notes.iter().enumerate() {
+        let mut error = None;
+        let md = match path {
+            Some(path) => links::rewrite(md, |link| {
+                if link.kind != Kind::Markdown {
+                    return None;
+                }
+                let to = index.resolve(link, path)?;
+                if let Some(href) = chapters.get(&to) {
+                    return Some(href.clone());
+                }
+                book.image(&to).unwrap_or_else(|err| {
+                    error.get_or_insert(err);
+                    None
+                })
+            }),
+            None => md.clone(),
+        };
+        if let Some(err) = error {
+            return Err(err);
+        }
+
+        let title = note::title(&md, path.as_deref()).unwrap_or_else(|| format!("{}", i + 1));
+        book.chapter(&title, &html::fragment(&md)?);
+    }
+
+    let mut epub = Cursor::new(Vec::new());
+    book.write(&mut epub)?;
+
+    Ok(epub.into_inner())
+}
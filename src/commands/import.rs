@@ -0,0 +1,301 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    args::{ImportCmd, ImportFormat},
+    config::Config,
+    frontmatter,
+    git::Repo,
+    import::{self, Item, Problem},
+    links::{self, Index, Kind},
+    note,
+    path::create_unique,
+};
+
+/// Where attachments are copied to in the atoms directory.
+const ATTACHMENTS_DIR: &str = "attachments";
+
+/// Command called to import notes from another note app as atoms.
+///
+/// Every note is given its file before any are written, so that links between the imported notes
+/// can be pointed at their new paths. A note that can't be converted is left out and reported,
+/// rather than stopping the import.
+pub fn import(cmd: &ImportCmd, config: &Config) -> Result<()> {
+    let root = config
+        .root()
+        .canonicalize()
+        .with_context(|| format!("Notes root {} not found", config.root().to_string_lossy()))?;
+    let source = cmd
+        .source
+        .canonicalize()
+        .with_context(|| format!("Source {} not found", cmd.source.to_string_lossy()))?;
+    let timezone = config.create.timezone;
+    let (format, import) = match cmd.format {
+        ImportFormat::Enex => ("enex", import::enex(&source, timezone)?),
+        _ if !source.is_dir() => bail!("{} is not a directory", source.to_string_lossy()),
+        _ if source.starts_with(&root) || root.starts_with(&source) => bail!(
+            "Unable to import from {}, which overlaps the notes root",
+            source.to_string_lossy()
+        ),
+        ImportFormat::Obsidian => ("obsidian", import::obsidian(&source, timezone)?),
+        ImportFormat::Markdown => ("markdown", import::markdown(&source, timezone)?),
+        ImportFormat::Plain => ("plain", import::plain(&source, timezone)?),
+    };
+    let mut problems = import.problems;
+
+    // Date every note before creating any files, so that a bad timestamp format leaves none behind
+    let now = timezone.now();
+    let dated = import
+        .items
+        .into_iter()
+        .map(|item| {
+            let date = item.created.unwrap_or(now);
+            let timestamp = config.create.timestamp(&date)?;
+            Ok((item, config.atom_path(&date), timestamp))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // Give each note its file, dated by when it was created in the source
+    let mut placed = Vec::new();
+    for (item, path, timestamp) in dated {
+        match place(&path, &root) {
+            Ok(path) => placed.push((item, path, timestamp)),
+            Err(err) => {
+                unplace(&placed);
+                return Err(err);
+            }
+        }
+    }
+
+    let moved = placed
+        .iter()
+        .map(|(item, path, _)| (item.path.clone(), path.clone()))
+        .collect::<HashMap<_, _>>();
+    let before = Index::new(
+        if source.is_dir() {
+            &source
+        } else {
+            source.parent().unwrap_or(&source)
+        },
+        moved.keys().cloned().collect(),
+    );
+    let after = match note::walk(&root) {
+        Ok(notes) => Index::new(&root, notes),
+        Err(err) => {
+            unplace(&placed);
+            return Err(err);
+        }
+    };
+    let mut attachments = Attachments {
+        source: source.clone(),
+        dir: config.base_dir().join(ATTACHMENTS_DIR),
+        data: import.attachments,
+        copied: HashMap::new(),
+    };
+
+    let mut written = Vec::new();
+    for (item, path, timestamp) in &placed {
+        let mut relink = Relink {
+            item,
+            path,
+            before: &before,
+            after: &after,
+            moved: &moved,
+            attachments: &mut attachments,
+            problems: &mut problems,
+        };
+        let md = relink.run();
+        let written_note = compose(item, &md, timestamp, format)
+            .and_then(|text| Ok(fs::File::create(path)?.write_all(text.as_bytes())?));
+        match written_note {
+            Ok(()) => written.push(path.clone()),
+            Err(err) => {
+                fs::remove_file(path).ok();
+                problems.push(Problem {
+                    name: item.name.clone(),
+                    reason: format!("{:#}", err),
+                });
+            }
+        }
+    }
+
+    let count = |count: usize, unit: &str| match count {
+        1 => format!("1 {}", unit),
+        count => format!("{} {}s", count, unit),
+    };
+    println!(
+        "Imported {} and {} into {}",
+        count(written.len(), "note"),
+        count(attachments.copied.len(), "attachment"),
+        config.base_dir().to_string_lossy()
+    );
+    if !problems.is_empty() {
+        eprintln!("Could not convert {}:", count(problems.len(), "item"));
+        for problem in &problems {
+            eprintln!("  {}: {}", problem.name, problem.reason);
+        }
+    }
+
+    if config.git.auto_commit && !written.is_empty() {
+        let repo = Repo::open(config)?;
+        let paths = written
+            .iter()
+            .chain(attachments.copied.values())
+            .cloned()
+            .collect::<Vec<_>>();
+        repo.commit(
+            &paths,
+            &format!("Import {} from {}", count(written.len(), "note"), format),
+        )?;
+        if config.git.auto_sync {
+            repo.sync(&config.git.remote, true)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Create an empty file for a note at the atom path, or next to it when taken, returning its path.
+fn place(path: &Path, root: &Path) -> Result<PathBuf> {
+    let dir = path.parent().unwrap_or(root);
+    fs::create_dir_all(dir)?;
+    let path = dir
+        .canonicalize()?
+        .join(path.file_name().unwrap_or_default());
+    let (_, path) = create_unique(&path)?;
+
+    Ok(path)
+}
+
+/// Remove the files created for notes, when the import stops before writing them.
+fn unplace(placed: &[(Item, PathBuf, String)]) {
+    for (_, path, _) in placed {
+        fs::remove_file(path).ok();
+    }
+}
+
+/// Attachments copied into the atoms directory, each only once however many notes link to it.
+struct Attachments {
+    source: PathBuf,
+    dir: PathBuf,
+
+    /// Attachments that aren't files in the source, by the path links resolve to
+    data: HashMap<PathBuf, Vec<u8>>,
+
+    /// Where each attachment linked so far was copied to
+    copied: HashMap<PathBuf, PathBuf>,
+}
+
+impl Attachments {
+    /// Copy the attachment at the path links resolve to, if it is one, returning its new path.
+    fn copy(&mut self, from: &Path) -> Result<Option<PathBuf>> {
+        if let Some(to) = self.copied.get(from) {
+            return Ok(Some(to.clone()));
+        }
+        let is_file = from.starts_with(&self.source) && from.is_file();
+        if !self.data.contains_key(from) && !is_file {
+            return Ok(None);
+        }
+
+        fs::create_dir_all(&self.dir)?;
+        let name = from.file_name().unwrap_or_default();
+        let (mut file, to) = create_unique(&self.dir.canonicalize()?.join(name))?;
+        match self.data.get(from) {
+            Some(data) => file.write_all(data)?,
+            None => file.write_all(&fs::read(from)?)?,
+        }
+        self.copied.insert(from.to_path_buf(), to.clone());
+
+        Ok(Some(to))
+    }
+}
+
+/// Points the links in an imported note at where the notes and attachments they link to now are.
+struct Relink<'a> {
+    item: &'a Item,
+    path: &'a Path,
+    before: &'a Index,
+    after: &'a Index,
+    moved: &'a HashMap<PathBuf, PathBuf>,
+    attachments: &'a mut Attachments,
+    problems: &'a mut Vec<Problem>,
+}
+
+impl Relink<'_> {
+    fn run(&mut self) -> String {
+        let md = &self.item.md;
+        links::rewrite(md, |link| {
+            let from = self.before.resolve(link, &self.item.path)?;
+            if let Some(to) = self.moved.get(&from) {
+                let target = self.after.target(link, self.path, to);
+
+                // Wiki links show their target, so keep showing the old name as the label
+                return match link.kind == Kind::Wiki && md[link.range.end..].starts_with("]]") {
+                    true => Some(format!("{}|{}", target, link.target.trim())),
+                    false => Some(target),
+                };
+            }
+            if link.kind == Kind::Wiki {
+                return None;
+            }
+
+            match self.attachments.copy(&from) {
+                Ok(Some(to)) => Some(self.after.target(link, self.path, &to)),
+                Ok(None) => {
+                    if !from.exists() {
+                        self.problem(format!("Link to {} not found", link.target));
+                    }
+                    None
+                }
+                Err(err) => {
+                    self.problem(format!("Unable to copy {}: {:#}", link.target, err));
+                    None
+                }
+            }
+        })
+    }
+
+    fn problem(&mut self, reason: String) {
+        self.problems.push(Problem {
+            name: self.item.name.clone(),
+            reason,
+        });
+    }
+}
+
+/// The imported note, with front matter giving its timestamp and title ahead of any front matter
+/// it came with.
+fn compose(item: &Item, md: &str, timestamp: &str, format: &str) -> Result<String> {
+    let md = frontmatter::unset(md, "timestamp");
+    let front_matter = frontmatter::parse(&md)?;
+    let (yaml, body) = frontmatter::split(&md);
+
+    let mut text = format!("---\ntimestamp: {}\n", frontmatter::scalar(timestamp)?);
+    if let Some(title) = item
+        .title
+        .as_ref()
+        .filter(|_| !front_matter.contains_key("title"))
+    {
+        text.push_str(&format!("title: {}\n", frontmatter::scalar(title)?));
+    }
+    if let Some(yaml) = yaml.filter(|yaml| !yaml.trim().is_empty()) {
+        text.push_str(yaml.trim_end());
+        text.push('\n');
+    }
+    text.push_str(&format!("imported-from: {}\n---\n", format));
+    if yaml.is_none() {
+        text.push('\n');
+    }
+    text.push_str(body);
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+
+    Ok(text)
+}
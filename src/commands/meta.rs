@@ -0,0 +1,79 @@
+use std::fs;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    args::{MetaAction, MetaCmd},
+    bulk,
+    config::Config,
+    frontmatter,
+};
+
+/// Command called to set, remove, or rename a front matter key across notes.
+///
+/// Every selected note is checked before any are written, and one whose front matter would no
+/// longer parse stops the whole change.
+pub fn meta(cmd: &MetaCmd, config: &Config) -> Result<()> {
+    let root = config
+        .root()
+        .canonicalize()
+        .with_context(|| format!("Notes root {} not found", config.root().to_string_lossy()))?;
+    let (selection, write) = match &cmd.action {
+        MetaAction::Set {
+            selection, write, ..
+        }
+        | MetaAction::Unset {
+            selection, write, ..
+        }
+        | MetaAction::RenameKey {
+            selection, write, ..
+        } => (selection, write),
+    };
+
+    let mut changes = Vec::new();
+    for path in bulk::select(selection, &root, &config.create)? {
+        let md = fs::read_to_string(&path)
+            .with_context(|| format!("Unable to read note {}", path.to_string_lossy()))?;
+        let updated = match &cmd.action {
+            MetaAction::Set {
+                key,
+                value,
+                missing,
+                ..
+            } => {
+                let front_matter = frontmatter::parse(&md).with_context(|| {
+                    format!("Unable to read front matter in {}", path.to_string_lossy())
+                })?;
+                match *missing && front_matter.contains_key(key.as_str()) {
+                    true => md.clone(),
+                    false => {
+                        let value = match bulk::has_placeholders(value) {
+                            true => frontmatter::scalar(&bulk::expand(
+                                value,
+                                &md,
+                                &path,
+                                &root,
+                                &config.create,
+                            ))?,
+                            false => frontmatter::value(value)?,
+                        };
+                        frontmatter::set(&md, key, &value)
+                    }
+                }
+            }
+            MetaAction::Unset { key, .. } => frontmatter::unset(&md, key),
+            MetaAction::RenameKey { from, to, .. } => frontmatter::rename(&md, from, to)
+                .with_context(|| format!("Unable to update {}", path.to_string_lossy()))?,
+        };
+
+        frontmatter::parse(&updated).with_context(|| {
+            format!(
+                "The change would leave invalid front matter in {}",
+                path.to_string_lossy()
+            )
+        })?;
+        changes.push((path, md, updated));
+    }
+
+    bulk::write(&root, &changes, write, config.create.timezone)
+}
@@ -0,0 +1,229 @@
+use std::{fmt::Write as _, fs};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+
+use crate::{
+    args::MigrateCmd,
+    bulk,
+    config::{Config, Timezone},
+    frontmatter,
+    query::{Expr, Fields, Value},
+};
+
+/// The date formats tried when reading a date to reformat, after RFC 3339.
+const DATE_FORMATS: [&str; 4] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y%m%d%H%M%S",
+];
+
+/// A migration script, as read from the TOML file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Script {
+    #[serde(default)]
+    step: Vec<RawStep>,
+}
+
+/// A step as written in the script, before checking it names exactly one change.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawStep {
+    set: Option<String>,
+    unset: Option<String>,
+    rename: Option<String>,
+    date: Option<String>,
+    value: Option<String>,
+    to: Option<String>,
+    format: Option<String>,
+    from: Option<String>,
+    #[serde(default)]
+    missing: bool,
+    #[serde(rename = "where")]
+    condition: Option<String>,
+}
+
+/// A change made by one step.
+enum Action {
+    Set {
+        key: String,
+        value: String,
+        missing: bool,
+    },
+    Unset(String),
+    Rename {
+        from: String,
+        to: String,
+    },
+    Date {
+        key: String,
+        format: String,
+        from: Option<String>,
+    },
+}
+
+struct Step {
+    action: Action,
+    condition: Option<Expr>,
+}
+
+/// Command called to apply a migration script to the front matter of many notes.
+///
+/// Steps are applied in order to each note, so a step sees the changes made by the steps before
+/// it, including in its `where` condition.
+pub fn migrate(cmd: &MigrateCmd, config: &Config) -> Result<()> {
+    let root = config
+        .root()
+        .canonicalize()
+        .with_context(|| format!("Notes root {} not found", config.root().to_string_lossy()))?;
+    let script = fs::read_to_string(&cmd.script)
+        .with_context(|| format!("Unable to read script {}", cmd.script.to_string_lossy()))?;
+    let steps = steps(&script)
+        .with_context(|| format!("Invalid script {}", cmd.script.to_string_lossy()))?;
+
+    let mut changes = Vec::new();
+    let timezone = config.create.timezone;
+    for path in bulk::select(&cmd.selection, &root, &config.create)? {
+        let md = fs::read_to_string(&path)
+            .with_context(|| format!("Unable to read note {}", path.to_string_lossy()))?;
+        let mut updated = md.clone();
+        for (i, step) in steps.iter().enumerate() {
+            let front_matter = frontmatter::parse(&updated).with_context(|| {
+                format!(
+                    "Invalid front matter in {} before step {}",
+                    path.to_string_lossy(),
+                    i + 1
+                )
+            })?;
+            let fields = Fields::new(&updated, &path, &root, &config.create);
+            if step
+                .condition
+                .as_ref()
+                .is_some_and(|condition| !condition.matches(&fields))
+            {
+                continue;
+            }
+
+            updated = match &step.action {
+                Action::Set {
+                    key,
+                    value,
+                    missing,
+                } => match *missing && front_matter.contains_key(key.as_str()) {
+                    true => updated,
+                    false => {
+                        let value = match bulk::has_placeholders(value) {
+                            true => frontmatter::scalar(&bulk::expand(
+                                value,
+                                &updated,
+                                &path,
+                                &root,
+                                &config.create,
+                            ))?,
+                            false => frontmatter::value(value)?,
+                        };
+                        frontmatter::set(&updated, key, &value)
+                    }
+                },
+                Action::Unset(key) => frontmatter::unset(&updated, key),
+                Action::Rename { from, to } => frontmatter::rename(&updated, from, to)
+                    .with_context(|| format!("Unable to update {}", path.to_string_lossy()))?,
+                Action::Date { key, format, from } => match fields.get(key) {
+                    Value::Null => updated,
+                    value => {
+                        let date = reformat(&value.to_string(), format, from.as_deref(), timezone)
+                            .with_context(|| {
+                                format!("Unable to read {} in {}", key, path.to_string_lossy())
+                            })?;
+                        frontmatter::set(&updated, key, &frontmatter::scalar(&date)?)
+                    }
+                },
+            };
+        }
+
+        frontmatter::parse(&updated).with_context(|| {
+            format!(
+                "The script would leave invalid front matter in {}",
+                path.to_string_lossy()
+            )
+        })?;
+        changes.push((path, md, updated));
+    }
+
+    bulk::write(&root, &changes, &cmd.write, config.create.timezone)
+}
+
+/// Read the steps in a script, checking each makes exactly one change.
+fn steps(script: &str) -> Result<Vec<Step>> {
+    let script: Script = toml::from_str(script)?;
+    script
+        .step
+        .into_iter()
+        .enumerate()
+        .map(|(i, raw)| {
+            let action = match (raw.set, raw.unset, raw.rename, raw.date) {
+                (Some(key), None, None, None) => Action::Set {
+                    key,
+                    value: raw
+                        .value
+                        .with_context(|| format!("Step {} sets a key without a value", i + 1))?,
+                    missing: raw.missing,
+                },
+                (None, Some(key), None, None) => Action::Unset(key),
+                (None, None, Some(from), None) => Action::Rename {
+                    from,
+                    to: raw
+                        .to
+                        .with_context(|| format!("Step {} renames a key without a to", i + 1))?,
+                },
+                (None, None, None, Some(key)) => Action::Date {
+                    key,
+                    format: raw.format.with_context(|| {
+                        format!("Step {} reformats a date without a format", i + 1)
+                    })?,
+                    from: raw.from,
+                },
+                _ => bail!(
+                    "Step {} must have exactly one of set, unset, rename, or date",
+                    i + 1
+                ),
+            };
+            let condition = raw.condition.as_deref().map(Expr::parse).transpose()?;
+
+            Ok(Step { action, condition })
+        })
+        .collect()
+}
+
+/// Read a date in any of the known formats, or the given one, and write it in the new format.
+///
+/// Dates with an offset keep it, and dates without one are taken to be in the timezone new notes
+/// are dated in.
+fn reformat(date: &str, format: &str, from: Option<&str>, timezone: Timezone) -> Result<String> {
+    let parsed = match DateTime::parse_from_rfc3339(date) {
+        Ok(date) => Some(date),
+        Err(_) => from
+            .into_iter()
+            .chain(DATE_FORMATS)
+            .find_map(|format| NaiveDateTime::parse_from_str(date, format).ok())
+            .or_else(|| {
+                from.into_iter()
+                    .chain(["%Y-%m-%d"])
+                    .find_map(|format| NaiveDate::parse_from_str(date, format).ok())
+                    .and_then(|date| date.and_hms_opt(0, 0, 0))
+            })
+            .and_then(|date| timezone.at(&date)),
+    };
+    let Some(parsed) = parsed else {
+        bail!("Unrecognized date {}", date);
+    };
+
+    let mut formatted = String::new();
+    write!(formatted, "{}", parsed.format(format))
+        .map_err(|_| anyhow!("Invalid date format '{}'", format))?;
+
+    Ok(formatted)
+}
@@ -1,7 +1,39 @@
+mod archive;
+mod commit;
+mod config;
 mod create;
+mod edit;
+mod export;
+mod import;
+mod meta;
+mod migrate;
+mod mv;
+mod query;
 mod render_html;
 mod render_pdf;
+mod review;
+mod rm;
+mod stats;
+mod sync;
+mod tasks;
+mod trash;
 
+pub use archive::*;
+pub use commit::*;
+pub use config::*;
 pub use create::*;
+pub use edit::*;
+pub use export::*;
+pub use import::*;
+pub use meta::*;
+pub use migrate::*;
+pub use mv::*;
+pub use query::*;
 pub use render_html::*;
 pub use render_pdf::*;
+pub use review::*;
+pub use rm::*;
+pub use stats::*;
+pub use sync::*;
+pub use tasks::*;
+pub use trash::*;
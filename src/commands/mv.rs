@@ -0,0 +1,154 @@
+use std::{
+    fs::{self, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use similar::TextDiff;
+
+use crate::{
+    args::MvCmd,
+    config::Config,
+    links::{self, Index, Kind},
+    note,
+    path::{locate, locate_note},
+};
+
+/// Command called to move or rename a note, updating links to it.
+///
+/// Wiki links and relative Markdown links in other notes that point at the note are rewritten to
+/// its new location, as are the note's own relative links when it changes directory. With
+/// --dry-run the changes are shown as a diff instead of being made.
+pub fn mv(args: &MvCmd, config: &Config) -> Result<()> {
+    let root = config
+        .root()
+        .canonicalize()
+        .with_context(|| format!("Notes root {} not found", config.root().to_string_lossy()))?;
+
+    let from = locate_note(&args.from, &root)?;
+    let to = destination(&args.to, &from, &root)?;
+    if !to.starts_with(&root) {
+        bail!(
+            "{} is outside the notes root {}",
+            to.to_string_lossy(),
+            root.to_string_lossy()
+        );
+    }
+    if to.exists() {
+        bail!("{} already exists", to.to_string_lossy());
+    }
+
+    // Resolve links against the notes as they are now, and write them for the notes after the move
+    let before = Index::new(&root, note::walk(&root)?);
+    let after = before.moved(&from, &to);
+
+    // Links by title or alias still find the note wherever it goes
+    let moves_with =
+        |link: &links::Link, note: &Path| after.resolve(link, note).as_ref() == Some(&to);
+
+    let mut changes = Vec::new();
+    for note in before.notes() {
+        let md = fs::read_to_string(note)?;
+        let updated = match note == &from {
+            true => links::rewrite(&md, |link| {
+                let target = before.resolve(link, &from)?;
+                // Wiki links don't depend on where the note is, unless they point to itself by
+                // its filename or path
+                if link.kind == Kind::Wiki && (target != from || moves_with(link, &to)) {
+                    return None;
+                }
+                let target = if target == from { &to } else { &target };
+                Some(after.target(link, &to, target)).filter(|new| new != &link.target)
+            }),
+            false => links::rewrite(&md, |link| {
+                (before.resolve(link, note)? == from && !moves_with(link, note))
+                    .then(|| after.target(link, note, &to))
+                    .filter(|new| new != &link.target)
+            }),
+        };
+        if updated != md || note == &from {
+            changes.push((note.clone(), md, updated));
+        }
+    }
+
+    let display = |path: &Path| {
+        path.strip_prefix(&root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    if args.dry_run {
+        println!("Move {} to {}", display(&from), display(&to));
+        for (note, md, updated) in &changes {
+            if md == updated {
+                continue;
+            }
+            let new_path = if note == &from { &to } else { note };
+            print!(
+                "{}",
+                TextDiff::from_lines(md, updated)
+                    .unified_diff()
+                    .header(&display(note), &display(new_path))
+            );
+        }
+        return Ok(());
+    }
+
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Claim the destination before moving onto it, so a note created there since it was checked is
+    // never replaced
+    match OpenOptions::new().create_new(true).write(true).open(&to) {
+        Ok(_) => {}
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            bail!("{} already exists", to.to_string_lossy())
+        }
+        Err(err) => {
+            return Err(err).with_context(|| format!("Unable to create {}", to.to_string_lossy()))
+        }
+    }
+    if let Err(err) = fs::rename(&from, &to) {
+        let _ = fs::remove_file(&to);
+        return Err(err).with_context(|| {
+            format!(
+                "Unable to move {} to {}",
+                from.to_string_lossy(),
+                to.to_string_lossy()
+            )
+        });
+    }
+    println!("Moved {} to {}", display(&from), display(&to));
+
+    for (note, md, updated) in changes {
+        if md == updated {
+            continue;
+        }
+        let path = if note == from { &to } else { &note };
+        fs::write(path, updated)
+            .with_context(|| format!("Unable to update links in {}", path.to_string_lossy()))?;
+        println!("Updated links in {}", display(path));
+    }
+
+    Ok(())
+}
+
+/// The full path to move the note to. Moving into a directory keeps the filename, and a missing
+/// extension is added.
+fn destination(to: &Path, from: &Path, root: &Path) -> Result<PathBuf> {
+    let mut path = locate(to, root)?;
+    let into_dir = path.is_dir() || to.to_string_lossy().ends_with(['/', '\\']);
+    if into_dir {
+        path.push(from.file_name().context("The note has no filename")?);
+    } else if path.extension().is_none() {
+        path.set_extension("md");
+    }
+
+    // Canonicalize through the parent so the path matches the notes found under the root
+    match path.parent().and_then(|parent| parent.canonicalize().ok()) {
+        Some(parent) => Ok(parent.join(path.file_name().context("Invalid destination")?)),
+        None => Ok(path),
+    }
+}
@@ -0,0 +1,134 @@
+use std::fs;
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    args::{QueryCmd, QueryFormat},
+    config::Config,
+    note,
+    query::{self, Expr, Fields, Value},
+};
+
+/// Command called to find notes matching a front matter query.
+pub fn query(args: &QueryCmd, config: &Config) -> Result<()> {
+    let root = config
+        .root()
+        .canonicalize()
+        .with_context(|| format!("Notes root {} not found", config.root().to_string_lossy()))?;
+    let dir = match &args.subdir {
+        Some(subdir) => {
+            let dir = root.join(subdir);
+            if !dir.is_dir() {
+                bail!("Directory {} not found", dir.to_string_lossy());
+            }
+            dir
+        }
+        None => root.clone(),
+    };
+    let expr = args.query.as_deref().map(Expr::parse).transpose()?;
+
+    let mut found = Vec::new();
+    for path in note::walk(&dir)? {
+        let md = fs::read_to_string(&path)
+            .with_context(|| format!("Unable to read note {}", path.to_string_lossy()))?;
+        let fields = Fields::new(&md, &path, &root, &config.create);
+        if expr.as_ref().is_none_or(|expr| expr.matches(&fields)) {
+            found.push((path, fields));
+        }
+    }
+
+    found.sort_by(|(_, a), (_, b)| {
+        args.sort
+            .iter()
+            .map(|field| query::sort_order(&a.get(field), &b.get(field), &config.create))
+            .find(|order| order.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    if args.reverse {
+        found.reverse();
+    }
+    if let Some(limit) = args.limit {
+        found.truncate(limit);
+    }
+
+    let rows = found
+        .iter()
+        .map(|(_, fields)| {
+            args.fields
+                .iter()
+                .map(|field| fields.get(field))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    match args.format {
+        QueryFormat::Paths => {
+            for (path, _) in &found {
+                println!("{}", path.to_string_lossy());
+            }
+        }
+        QueryFormat::Json => {
+            let notes = rows
+                .iter()
+                .map(|row| {
+                    args.fields
+                        .iter()
+                        .cloned()
+                        .zip(row.iter().map(Value::to_json))
+                        .collect::<serde_json::Map<_, _>>()
+                })
+                .collect::<Vec<_>>();
+            println!("{}", serde_json::to_string_pretty(&notes)?);
+        }
+        QueryFormat::Csv => {
+            println!("{}", csv_row(args.fields.iter().cloned()));
+            for row in &rows {
+                println!("{}", csv_row(row.iter().map(Value::to_string)));
+            }
+        }
+        QueryFormat::Table => {
+            let rows = rows
+                .iter()
+                .map(|row| row.iter().map(Value::to_string).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            let widths = args
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, field)| {
+                    rows.iter()
+                        .map(|row| row[i].chars().count())
+                        .chain([field.chars().count()])
+                        .max()
+                        .unwrap_or(0)
+                })
+                .collect::<Vec<_>>();
+
+            let line = |cells: &[String]| {
+                let padded = cells
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                    .collect::<Vec<_>>();
+                padded.join("  ").trim_end().to_string()
+            };
+            println!("{}", line(&args.fields));
+            for row in &rows {
+                println!("{}", line(row));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A line of CSV, quoting the cells that need it.
+fn csv_row(cells: impl Iterator<Item = String>) -> String {
+    cells
+        .map(|cell| match cell.contains([',', '"', '\n', '\r']) {
+            true => format!("\"{}\"", cell.replace('"', "\"\"")),
+            false => cell,
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
@@ -2,57 +2,99 @@ use std::{
     ffi::OsString,
     fs::{self, OpenOptions},
     io,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 
 use crate::{
     args::HtmlCmd,
     config::Config,
-    html::HtmlWriter,
-    path::{generate_output_path, read_md_from_stdin},
+    hooks::{self, Hook},
+    html::{stylesheets, theme, HtmlWriter},
+    note,
+    path::{check_outputs, generate_output_path, read_md_from_stdin, read_paths_from_stdin},
 };
 
 /// Command called to render HTML.
 ///
 /// Converts Markdown from the input argument to HTML and outputs on stdout by default, or to the
-/// file provided using the output argument. To avoid doubt, this will only process files with a
-/// `.md` extension. The destination directory must exist.
+/// file provided using the output argument, or renders each note listed on stdin to its own file.
+/// To avoid doubt, this will only process files with a `.md` extension. The destination directory
+/// must exist.
 pub fn render_html(args: &HtmlCmd, config: &Config) -> Result<()> {
+    if !args.paths {
+        return render(args.input.as_deref(), args, config);
+    }
+
+    let inputs = read_paths_from_stdin()?;
+    check_outputs(&args.output, inputs.len())?;
+    for input in &inputs {
+        render(Some(input), args, config)?;
+    }
+
+    Ok(())
+}
+
+/// Render the note at the input path, or from stdin if there isn't one.
+fn render(source: Option<&Path>, args: &HtmlCmd, config: &Config) -> Result<()> {
     // Find the file to render
-    let input = match &args.input {
-        Some(input) => input.canonicalize()?,
+    let input = match source {
+        Some(input) => input
+            .canonicalize()
+            .with_context(|| format!("Unable to find input file {}", input.to_string_lossy()))?,
         // This will require that an output file is provided if the -i flag is set or there won't be a valid output
         // filename to use, unless being output to stdout
         None => PathBuf::new(),
     };
-    if args.input.is_some() && input.extension() != Some(&OsString::from("md")) {
+    if source.is_some() && input.extension() != Some(&OsString::from("md")) {
         bail!("The file selected is not a markdown file");
     }
 
+    // Check the output path before reading the input string in case there are issues
+    let output_path = args
+        .output
+        .as_ref()
+        .map(|output| generate_output_path("html", output.clone(), &input))
+        .transpose()?;
+
+    let md = match source {
+        Some(_) => fs::read_to_string(&input)?,
+        None => read_md_from_stdin()?,
+    };
+    let source = source.map(|_| input.as_path());
+    let md = hooks::pre_render(config, source, output_path.as_deref(), md)?;
+    let css = stylesheets(config, &args.style, &md, source, false)?;
+
     // Dynamically dispatch on the type of writer
-    // Do this before reading the input string in case there are issues
-    let output_writer: Box<dyn io::Write> = match &args.output {
-        Some(output) => Box::new(
+    let output_writer: Box<dyn io::Write> = match &output_path {
+        Some(output_path) => Box::new(
             OpenOptions::new()
                 .create_new(true)
                 .write(true)
-                .open(generate_output_path("html", output.clone(), &input)?)?,
+                .open(output_path)?,
         ),
         None => Box::new(io::stdout()),
     };
-    let mut output_writer = HtmlWriter::new(output_writer, config.css.clone());
-
-    let md = match args.input {
-        Some(_) => fs::read_to_string(input)?,
-        None => read_md_from_stdin()?,
-    };
+    let mut output_writer = HtmlWriter::new(
+        output_writer,
+        note::title(&md, source),
+        theme(config, &args.style),
+        css,
+    );
 
     // Choose the output method based on whether we are producing raw results or not
     match args.raw {
         true => output_writer.write_markdown(&md)?,
         false => output_writer.write_html(&md)?,
     }
-    Ok(())
+    drop(output_writer);
+
+    hooks::run(
+        config,
+        Hook::PostRender,
+        source,
+        output_path.as_deref(),
+        &md,
+    )
 }
@@ -1,7 +1,7 @@
 use std::{
     fs::{self, OpenOptions},
     io::{self, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 use anyhow::{anyhow, bail, Context, Result};
@@ -11,24 +11,38 @@ use tempfile::Builder;
 use crate::{
     args::PdfCmd,
     config::Config,
-    html::HtmlWriter,
-    path::{generate_output_path, read_md_from_stdin},
+    hooks::{self, Hook},
+    html::{stylesheets, theme, HtmlWriter},
+    note,
+    path::{check_outputs, generate_output_path, read_md_from_stdin, read_paths_from_stdin},
 };
 
 /// Command called to render a PDF.
 ///
 /// Converts Markdown from the input argument to a PDF and outputs to the output file provided
-/// using the output argument. To avoid doubt, this will only process files with a`.md` extension.
-/// The destination directory must exist.
+/// using the output argument, or renders each note listed on stdin to its own file. To avoid
+/// doubt, this will only process files with a`.md` extension. The destination directory must
+/// exist.
 ///
 /// PERF: Get rid of all the PathBuf cloning
 pub fn render_pdf(args: &PdfCmd, config: &Config) -> Result<()> {
+    if !args.paths {
+        return render(args.input.as_deref(), args, config);
+    }
+
+    let inputs = read_paths_from_stdin()?;
+    check_outputs(&args.output, inputs.len())?;
+    for input in &inputs {
+        render(Some(input), args, config)?;
+    }
+
+    Ok(())
+}
+
+/// Render the note at the input path, or from stdin if there isn't one.
+fn render(input: Option<&Path>, args: &PdfCmd, config: &Config) -> Result<()> {
     // Find the file to render
-    let input = args
-        .input
-        .clone()
-        .map(|input| canonicalize_input_file(&input))
-        .transpose()?;
+    let input = input.map(canonicalize_input_file).transpose()?;
 
     // If the output option is provided, turn it into a file writer
     // Keep inside an option to replace with stdout otherwise
@@ -53,18 +67,24 @@ pub fn render_pdf(args: &PdfCmd, config: &Config) -> Result<()> {
         Some(input) => fs::read_to_string(input)?,
         None => read_md_from_stdin()?,
     };
-    HtmlWriter::new(&tmp_file, config.css.clone()).write_html(&md)?;
+    let md = hooks::pre_render(config, input.as_deref(), output_path.as_deref(), md)?;
+    let css = stylesheets(config, &args.style, &md, input.as_deref(), true)?;
+    HtmlWriter::new(
+        &tmp_file,
+        note::title(&md, input.as_deref()),
+        theme(config, &args.style),
+        css,
+    )
+    .write_html(&md)?;
 
     // We can immediately return the result as it appears that random temp will delete the file
-    match output_path {
+    match &output_path {
         Some(path) => {
-            let mut output_file_writer = OpenOptions::new()
-                .create_new(true)
-                .write(true)
-                .open(&path)?;
-            println!("Starting to convert pdf");
+            let mut output_file_writer =
+                OpenOptions::new().create_new(true).write(true).open(path)?;
+            eprintln!("Starting to convert pdf");
             convert_pdf(&mut output_file_writer, &tmp_file.path().to_string_lossy())?;
-            println!(
+            eprintln!(
                 "PDF conversion complete, output file at {}",
                 path.to_string_lossy()
             );
@@ -72,11 +92,17 @@ pub fn render_pdf(args: &PdfCmd, config: &Config) -> Result<()> {
         None => convert_pdf(&mut io::stdout(), &tmp_file.path().to_string_lossy())?,
     }
 
-    Ok(())
+    hooks::run(
+        config,
+        Hook::PostRender,
+        input.as_deref(),
+        output_path.as_deref(),
+        &md,
+    )
 }
 
 /// Take the input file name and canonicalize, noting that this will check for existence.
-fn canonicalize_input_file(original_input: &PathBuf) -> Result<PathBuf> {
+fn canonicalize_input_file(original_input: &Path) -> Result<PathBuf> {
     let input = original_input.canonicalize().with_context(|| {
         format!(
             "Invalid input path, unable to canonicalize {}",
@@ -0,0 +1,272 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Days, NaiveDate};
+
+use crate::{
+    args::{HtmlCmd, PdfCmd, ReviewCmd, StyleArgs},
+    config::{Config, Create},
+    git,
+    hooks::{self, Hook},
+    links, note,
+    tasks::{self, Task},
+};
+
+use super::{render_html, render_pdf};
+
+const DEFAULT_TEMPLATE: &str = include_str!("../templates/review.md");
+
+/// An atom written or changed during the review period.
+struct Entry {
+    path: PathBuf,
+    title: String,
+    created: Option<NaiveDate>,
+    tags: Vec<String>,
+    first_line: Option<String>,
+    tasks: Vec<Task>,
+}
+
+/// Command called to write a review note.
+///
+/// Atoms are included when they were created during the period, by their `timestamp` front matter
+/// or ID, or when their file was modified during it. The note is filled in from the template and
+/// can be rendered to HTML or PDF straight away.
+pub fn review(args: &ReviewCmd, config: &Config) -> Result<()> {
+    let timezone = config.create.timezone;
+    let today = timezone.now().date_naive();
+    let (period, start) = match (args.week, args.month, args.since) {
+        (true, _, _) => {
+            let start = today - Days::new(today.weekday().num_days_from_monday().into());
+            (today.format("%G-W%V").to_string(), start)
+        }
+        (_, true, _) => (
+            today.format("%Y-%m").to_string(),
+            today.with_day(1).unwrap(),
+        ),
+        (_, _, Some(since)) if since > today => bail!("{} is in the future", since),
+        (_, _, Some(since)) => (format!("{}_{}", since, today), since),
+        _ => unreachable!("Clap requires one of the periods"),
+    };
+
+    let root = config
+        .root()
+        .canonicalize()
+        .with_context(|| format!("Notes root {} not found", config.root().to_string_lossy()))?;
+    let dir = root.join(&config.review.dir);
+    let path = dir.join(format!("{}.md", period));
+    if path.exists() && !args.force {
+        bail!(
+            "Review {} already exists, use --force to overwrite it",
+            path.to_string_lossy()
+        );
+    }
+
+    let entries = match config.base_dir().canonicalize() {
+        Ok(atoms) => gather(&atoms, start, today, &config.create)?,
+        Err(_) => Vec::new(),
+    };
+
+    let template = match &config.review.template {
+        Some(template) => fs::read_to_string(template).with_context(|| {
+            format!(
+                "Unable to read review template {}",
+                template.to_string_lossy()
+            )
+        })?,
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+    let md = fill(
+        &template,
+        &[
+            ("{{period}}", period),
+            ("{{start}}", start.to_string()),
+            ("{{end}}", today.to_string()),
+            ("{{count}}", entries.len().to_string()),
+            ("{{atoms}}", atoms_section(&entries, &dir)),
+            ("{{tasks}}", tasks_section(&entries, &dir)),
+        ],
+    );
+
+    fs::create_dir_all(&dir)?;
+    fs::write(&path, &md)
+        .with_context(|| format!("Unable to write review {}", path.to_string_lossy()))?;
+    hooks::run(config, Hook::PostCreate, Some(&path), None, &md)?;
+    git::auto_commit(config, &path)?;
+
+    let style = || StyleArgs {
+        theme: None,
+        css: Vec::new(),
+        no_css: false,
+    };
+    if args.html {
+        replace(&path.with_extension("html"), args.force)?;
+        render_html(
+            &HtmlCmd {
+                input: Some(path.clone()),
+                paths: false,
+                output: Some(None),
+                raw: false,
+                style: style(),
+            },
+            config,
+        )?;
+    }
+    if args.pdf {
+        replace(&path.with_extension("pdf"), args.force)?;
+        render_pdf(
+            &PdfCmd {
+                input: Some(path.clone()),
+                paths: false,
+                output: Some(None),
+                style: style(),
+            },
+            config,
+        )?;
+    }
+
+    println!("{}", path.to_string_lossy());
+
+    Ok(())
+}
+
+/// The atoms created or modified between the dates, inclusive, in the order they were written.
+fn gather(atoms: &Path, start: NaiveDate, end: NaiveDate, create: &Create) -> Result<Vec<Entry>> {
+    let during = |date: Option<NaiveDate>| date.is_some_and(|date| date >= start && date <= end);
+
+    let mut entries = Vec::new();
+    for path in note::walk(atoms)? {
+        let md = fs::read_to_string(&path)
+            .with_context(|| format!("Unable to read note {}", path.to_string_lossy()))?;
+        let created = note::created(&md, &path, create);
+        if !during(created) && !during(note::modified(&path, create.timezone)) {
+            continue;
+        }
+
+        entries.push(Entry {
+            title: note::title(&md, Some(&path)).unwrap_or_default(),
+            created,
+            tags: note::tags(&md),
+            first_line: first_line(&md),
+            tasks: tasks::read(&path)?
+                .into_iter()
+                .filter(|task| !task.done)
+                .collect(),
+            path,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// A list of the atoms, each with its title, date, tags, and first line.
+fn atoms_section(entries: &[Entry], dir: &Path) -> String {
+    if entries.is_empty() {
+        return "None".to_string();
+    }
+
+    entries
+        .iter()
+        .map(|entry| {
+            let mut details = entry
+                .created
+                .map(|date| date.to_string())
+                .into_iter()
+                .collect::<Vec<_>>();
+            if !entry.tags.is_empty() {
+                details.push(format!("tags: {}", entry.tags.join(", ")));
+            }
+
+            let mut line = link(entry, dir);
+            if !details.is_empty() {
+                line.push_str(&format!(" ({})", details.join(", ")));
+            }
+            if let Some(first_line) = &entry.first_line {
+                line.push_str(&format!(": {}", first_line));
+            }
+            format!("- {}", line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A list of the open tasks in the atoms, each linking to the atom it is in.
+fn tasks_section(entries: &[Entry], dir: &Path) -> String {
+    let lines = entries
+        .iter()
+        .flat_map(|entry| {
+            entry.tasks.iter().map(move |task| {
+                let mut line = String::from("- ");
+                if let Some(priority) = task.priority {
+                    line.push_str(&format!("{} ", priority));
+                }
+                line.push_str(&task.text);
+                if let Some(due) = task.due {
+                    line.push_str(&format!(" (due {})", due));
+                }
+                line.push_str(&format!(", from {}", link(entry, dir)));
+                line
+            })
+        })
+        .collect::<Vec<_>>();
+
+    match lines.is_empty() {
+        true => "None".to_string(),
+        false => lines.join("\n"),
+    }
+}
+
+/// Fill in the placeholders in the template in a single pass, so that placeholders in the text
+/// filled in, such as in the title of an atom, are left as they are.
+fn fill(template: &str, values: &[(&str, String)]) -> String {
+    let mut md = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        md.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match values.iter().find(|(name, _)| rest.starts_with(name)) {
+            Some((name, value)) => {
+                md.push_str(value);
+                rest = &rest[name.len()..];
+            }
+            None => {
+                md.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    md.push_str(rest);
+
+    md
+}
+
+/// A Markdown link to the atom from the review directory, labelled with its title.
+fn link(entry: &Entry, dir: &Path) -> String {
+    let target = links::relative(dir, &entry.path)
+        .to_string_lossy()
+        .replace('\\', "/")
+        .replace(' ', "%20");
+    format!("[{}]({})", entry.title.replace(['[', ']'], ""), target)
+}
+
+/// The first line of the note's text, skipping headings and blank lines.
+fn first_line(md: &str) -> Option<String> {
+    note::prose(md)
+        .map(|(_, line)| line.trim())
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+}
+
+/// Check that a rendered file can be written, removing the existing one when forced.
+fn replace(output: &Path, force: bool) -> Result<()> {
+    match (output.exists(), force) {
+        (false, _) => Ok(()),
+        (true, true) => Ok(fs::remove_file(output)?),
+        (true, false) => bail!(
+            "{} already exists, use --force to overwrite it",
+            output.to_string_lossy()
+        ),
+    }
+}
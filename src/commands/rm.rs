@@ -0,0 +1,40 @@
+use anyhow::{Context, Result};
+
+use crate::{
+    args::RmCmd,
+    config::Config,
+    links::{self, Index},
+    note,
+    path::locate_note,
+    trash::Trash,
+};
+
+/// Command called to remove notes by moving them to the trash.
+///
+/// Warns about links from other notes that will no longer work.
+pub fn rm(args: &RmCmd, config: &Config) -> Result<()> {
+    let root = config
+        .root()
+        .canonicalize()
+        .with_context(|| format!("Notes root {} not found", config.root().to_string_lossy()))?;
+
+    // Find every note first so that nothing is removed if any of them are missing
+    let notes = args
+        .notes
+        .iter()
+        .map(|path| locate_note(path, &root))
+        .collect::<Result<Vec<_>>>()?;
+    let index = Index::new(&root, note::walk(&root)?);
+    let mut trash = Trash::open(&root, config.create.timezone)?;
+
+    for note in notes {
+        links::warn_incoming(&index, &note);
+        let entry = trash.put(&note)?;
+        println!(
+            "Moved {} to the trash, restore it with `jot trash restore {}`",
+            entry.path, entry.id
+        );
+    }
+
+    Ok(())
+}
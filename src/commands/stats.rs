@@ -0,0 +1,313 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::{Days, NaiveDate};
+use serde::Serialize;
+
+use crate::{
+    args::{Period, StatsCmd},
+    config::Config,
+    frontmatter,
+    links::{self, Index},
+    note,
+};
+
+/// The widest a bar in the histogram can be.
+const BAR_WIDTH: usize = 40;
+
+/// A note counted in the statistics.
+struct Counted {
+    path: PathBuf,
+    date: Option<NaiveDate>,
+    words: usize,
+    characters: usize,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Stats {
+    notes: usize,
+    words: usize,
+    characters: usize,
+    first: Option<String>,
+    last: Option<String>,
+    streak: Streak,
+    periods: Vec<Count>,
+    tags: Vec<Count>,
+    linked: Vec<Count>,
+    longest: Vec<Count>,
+}
+
+/// Consecutive days with at least one note created.
+#[derive(Debug, Serialize)]
+struct Streak {
+    /// The streak running up to today, or yesterday if nothing is written yet today
+    current: usize,
+    longest: usize,
+}
+
+/// A named count, such as the number of notes with a tag.
+#[derive(Debug, Serialize)]
+struct Count {
+    name: String,
+    count: usize,
+}
+
+/// Command called to show statistics about the notes.
+///
+/// Notes are dated by their `timestamp` front matter or atom ID, falling back to when they were
+/// last modified. Words and characters are counted in the body, leaving out the front matter.
+pub fn stats(args: &StatsCmd, config: &Config) -> Result<()> {
+    let root = config
+        .root()
+        .canonicalize()
+        .with_context(|| format!("Notes root {} not found", config.root().to_string_lossy()))?;
+    let dir = match &args.subdir {
+        Some(subdir) => {
+            let dir = root.join(subdir);
+            if !dir.is_dir() {
+                bail!("Directory {} not found", dir.to_string_lossy());
+            }
+            dir
+        }
+        None => root.clone(),
+    };
+
+    let mut notes = Vec::new();
+    for path in note::walk(&dir)? {
+        let md = fs::read_to_string(&path)
+            .with_context(|| format!("Unable to read note {}", path.to_string_lossy()))?;
+        let date = note::created(&md, &path, &config.create)
+            .or_else(|| note::modified(&path, config.create.timezone));
+        let after = args
+            .from
+            .is_none_or(|from| date.is_some_and(|date| date >= from));
+        let before = args.to.is_none_or(|to| date.is_some_and(|date| date <= to));
+        if !after || !before {
+            continue;
+        }
+
+        let body = frontmatter::split(&md).1;
+        notes.push(Counted {
+            date,
+            words: body.split_whitespace().count(),
+            characters: body.chars().count(),
+            tags: note::tags(&md),
+            path,
+        });
+    }
+
+    let display = |path: &Path| {
+        path.strip_prefix(&root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned()
+    };
+    let dates = notes
+        .iter()
+        .filter_map(|note| note.date)
+        .collect::<BTreeSet<_>>();
+
+    let mut longest = notes
+        .iter()
+        .map(|note| Count {
+            name: display(&note.path),
+            count: note.words,
+        })
+        .collect::<Vec<_>>();
+    longest.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    longest.truncate(args.top);
+
+    let stats = Stats {
+        notes: notes.len(),
+        words: notes.iter().map(|note| note.words).sum(),
+        characters: notes.iter().map(|note| note.characters).sum(),
+        first: dates.first().map(NaiveDate::to_string),
+        last: dates.last().map(NaiveDate::to_string),
+        streak: streak(&dates, config.create.timezone.now().date_naive()),
+        periods: periods(&dates, &notes, args.by),
+        tags: top(
+            notes.iter().flat_map(|note| note.tags.iter().cloned()),
+            args.top,
+        ),
+        linked: top(
+            linked(&root, &notes)?.iter().map(|path| display(path)),
+            args.top,
+        ),
+        longest,
+    };
+
+    match args.json {
+        true => println!("{}", serde_json::to_string_pretty(&stats)?),
+        false => print(&stats, args.by),
+    }
+
+    Ok(())
+}
+
+/// The number of notes in each period from the first note to the last, including empty periods so
+/// that gaps show in the histogram.
+fn periods(dates: &BTreeSet<NaiveDate>, notes: &[Counted], by: Period) -> Vec<Count> {
+    let key = |date: NaiveDate| match by {
+        Period::Day => date.format("%F").to_string(),
+        Period::Week => date.format("%G-W%V").to_string(),
+        Period::Month => date.format("%Y-%m").to_string(),
+    };
+
+    let mut counts = BTreeMap::new();
+    if let (Some(&first), Some(&last)) = (dates.first(), dates.last()) {
+        for date in first.iter_days().take_while(|date| *date <= last) {
+            counts.insert(key(date), 0);
+        }
+    }
+    for date in notes.iter().filter_map(|note| note.date) {
+        *counts.entry(key(date)).or_default() += 1;
+    }
+
+    counts
+        .into_iter()
+        .map(|(name, count)| Count { name, count })
+        .collect()
+}
+
+/// The current and longest runs of consecutive days in the dates.
+fn streak(dates: &BTreeSet<NaiveDate>, today: NaiveDate) -> Streak {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for &date in dates {
+        run = match previous.and_then(|previous| previous.checked_add_days(Days::new(1))) {
+            Some(next) if next == date => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(date);
+    }
+
+    // A streak is still going if the last note was written today or yesterday
+    let current = match previous {
+        Some(last) if last == today || last.checked_add_days(Days::new(1)) == Some(today) => run,
+        _ => 0,
+    };
+
+    Streak { current, longest }
+}
+
+/// The counted notes each note in the root links to, once per linking note.
+fn linked(root: &Path, notes: &[Counted]) -> Result<Vec<PathBuf>> {
+    let counted = notes
+        .iter()
+        .map(|note| note.path.as_path())
+        .collect::<BTreeSet<_>>();
+    let index = Index::new(root, note::walk(root)?);
+
+    let mut targets = Vec::new();
+    for from in index.notes() {
+        let Ok(md) = fs::read_to_string(from) else {
+            continue;
+        };
+        let linked = links::parse(&md)
+            .iter()
+            .filter_map(|link| index.resolve(link, from))
+            .filter(|target| target != from && counted.contains(target.as_path()))
+            .collect::<BTreeSet<_>>();
+        targets.extend(linked);
+    }
+
+    Ok(targets)
+}
+
+/// The most common values, most common first, then by name.
+fn top(values: impl Iterator<Item = String>, limit: usize) -> Vec<Count> {
+    let mut counts = HashMap::<String, usize>::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+
+    let mut counts = counts
+        .into_iter()
+        .map(|(name, count)| Count { name, count })
+        .collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    counts.truncate(limit);
+
+    counts
+}
+
+/// Print the statistics as text, with a histogram of notes per period.
+fn print(stats: &Stats, by: Period) {
+    let days = |count: usize| match count {
+        1 => "1 day".to_string(),
+        count => format!("{} days", count),
+    };
+
+    println!("Notes       {}", stats.notes);
+    println!("Words       {}", stats.words);
+    println!("Characters  {}", stats.characters);
+    if let (Some(first), Some(last)) = (&stats.first, &stats.last) {
+        println!("Dates       {} to {}", first, last);
+    }
+    println!(
+        "Streak      {} (longest {})",
+        days(stats.streak.current),
+        days(stats.streak.longest)
+    );
+
+    let heading = match by {
+        Period::Day => "Notes per day",
+        Period::Week => "Notes per week",
+        Period::Month => "Notes per month",
+    };
+    let max = stats.periods.iter().map(|period| period.count).max();
+    if let Some(max) = max {
+        println!("\n{}", heading);
+        let width = stats
+            .periods
+            .iter()
+            .map(|p| p.name.len())
+            .max()
+            .unwrap_or(0);
+        for period in &stats.periods {
+            // Round up so that any period with notes gets at least some bar
+            let bar = (period.count * BAR_WIDTH).div_ceil(max.max(1));
+            println!(
+                "  {:width$}  {:bar_width$}  {}",
+                period.name,
+                "#".repeat(bar),
+                period.count,
+                width = width,
+                bar_width = BAR_WIDTH
+            );
+        }
+    }
+
+    for (heading, counts, unit) in [
+        ("Top tags", &stats.tags, "note"),
+        ("Most linked", &stats.linked, "backlink"),
+        ("Longest", &stats.longest, "word"),
+    ] {
+        if counts.is_empty() {
+            continue;
+        }
+        println!("\n{}", heading);
+        let width = counts
+            .iter()
+            .map(|count| count.name.len())
+            .max()
+            .unwrap_or(0);
+        for count in counts {
+            println!(
+                "  {:width$}  {} {}{}",
+                count.name,
+                count.count,
+                unit,
+                if count.count == 1 { "" } else { "s" },
+                width = width
+            );
+        }
+    }
+}
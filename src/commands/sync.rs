@@ -0,0 +1,35 @@
+use anyhow::Result;
+
+use crate::{
+    args::SyncCmd,
+    config::Config,
+    git::{self, Repo},
+};
+
+/// Command called to sync the notes with the git remote.
+///
+/// Commits any changes under the notes root first so that the pull has a clean tree to rebase.
+pub fn sync(args: &SyncCmd, config: &Config) -> Result<()> {
+    let repo = Repo::open(config)?;
+
+    let changed = repo.changed()?;
+    if !changed.is_empty() {
+        let message = match &args.message {
+            Some(message) => message.clone(),
+            None => git::message(&changed)?,
+        };
+        repo.commit(&[], &message)?;
+        println!("Committed: {}", message);
+    }
+
+    let remote = &config.git.remote;
+    let synced = repo.sync(remote, !args.no_push)?;
+    if synced.pulled {
+        println!("Pulled from {}/{}", remote, synced.branch);
+    }
+    if synced.pushed {
+        println!("Pushed to {}/{}", remote, synced.branch);
+    }
+
+    Ok(())
+}
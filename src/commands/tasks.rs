@@ -0,0 +1,130 @@
+use std::{cmp::Ordering, path::Path};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    args::{TaskFilter, TasksAction, TasksCmd},
+    config::Config,
+    git, note,
+    tasks::{self, Task},
+};
+
+/// Command called to list, show an agenda of, or complete tasks.
+pub fn tasks(cmd: &TasksCmd, config: &Config) -> Result<()> {
+    let root = config
+        .root()
+        .canonicalize()
+        .with_context(|| format!("Notes root {} not found", config.root().to_string_lossy()))?;
+
+    match &cmd.action {
+        None => {
+            let tasks = find(&root, &cmd.filter)?;
+            let width = tasks.iter().map(|task| task.id().len()).max().unwrap_or(0);
+            for task in &tasks {
+                println!("{:width$}  {}", task.id(), describe(task), width = width);
+            }
+        }
+        Some(TasksAction::Agenda(filter)) => {
+            let today = config.create.timezone.now().date_naive();
+            let mut tasks = find(&root, filter)?
+                .into_iter()
+                .filter(|task| !task.done)
+                .filter_map(|task| Some((task.due?, task)))
+                .collect::<Vec<_>>();
+            tasks.sort_by_key(|(due, task)| (*due, task.priority.is_none(), task.priority));
+
+            let width = tasks
+                .iter()
+                .map(|(_, task)| task.id().len())
+                .max()
+                .unwrap_or(0);
+            let groups = [
+                ("Overdue", Ordering::Less),
+                ("Today", Ordering::Equal),
+                ("Upcoming", Ordering::Greater),
+            ];
+            let mut first = true;
+            for (heading, when) in groups {
+                let group = tasks
+                    .iter()
+                    .filter(|(due, _)| due.cmp(&today) == when)
+                    .collect::<Vec<_>>();
+                if group.is_empty() {
+                    continue;
+                }
+                if !first {
+                    println!();
+                }
+                first = false;
+                println!("{}", heading);
+                for (_, task) in group {
+                    println!("  {:width$}  {}", task.id(), describe(task), width = width);
+                }
+            }
+            if first {
+                println!("Nothing is due");
+            }
+        }
+        Some(TasksAction::Done { ids }) => {
+            for id in ids {
+                let task = tasks::complete(&root, id)?;
+                println!("Done {}  {}", id, task.text);
+                git::auto_commit(config, &task.path)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The tasks in the notes root that pass the filter, in the order of the notes they are in.
+fn find(root: &Path, filter: &TaskFilter) -> Result<Vec<Task>> {
+    let dir = match &filter.subdir {
+        Some(subdir) => {
+            let dir = root.join(subdir);
+            if !dir.is_dir() {
+                bail!("Directory {} not found", dir.to_string_lossy());
+            }
+            dir
+        }
+        None => root.to_path_buf(),
+    };
+    let tags = filter
+        .tags
+        .iter()
+        .map(|tag| tag.trim_start_matches('#').to_lowercase())
+        .collect::<Vec<_>>();
+
+    let mut found = Vec::new();
+    for path in note::walk(&dir)? {
+        found.extend(tasks::read(&path)?.into_iter().filter(|task| {
+            let status = match (filter.all, filter.done) {
+                (true, _) => true,
+                (false, done) => task.done == done,
+            };
+            let dated = match (filter.from, filter.to) {
+                (None, None) => true,
+                (from, to) => task.due.is_some_and(|due| {
+                    from.is_none_or(|from| due >= from) && to.is_none_or(|to| due <= to)
+                }),
+            };
+            status && dated && tags.iter().all(|tag| task.tags.contains(tag))
+        }));
+    }
+
+    Ok(found)
+}
+
+/// The task's checkbox, priority, text, and due date on one line.
+fn describe(task: &Task) -> String {
+    let mut line = format!("[{}]", if task.done { 'x' } else { ' ' });
+    if let Some(priority) = task.priority {
+        line.push_str(&format!(" {}", priority));
+    }
+    line.push_str(&format!(" {}", task.text));
+    if let Some(due) = task.due {
+        line.push_str(&format!(" (due {})", due));
+    }
+
+    line
+}
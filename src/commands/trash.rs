@@ -0,0 +1,59 @@
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    args::{TrashAction, TrashCmd},
+    config::Config,
+    trash::Trash,
+};
+
+/// Command called to manage the trash.
+pub fn trash(cmd: &TrashCmd, config: &Config) -> Result<()> {
+    let root = config
+        .root()
+        .canonicalize()
+        .with_context(|| format!("Notes root {} not found", config.root().to_string_lossy()))?;
+    let mut trash = Trash::open(&root, config.create.timezone)?;
+
+    match &cmd.action {
+        TrashAction::List => {
+            let entries = trash.entries();
+            let width = entries
+                .iter()
+                .map(|entry| entry.id.len())
+                .max()
+                .unwrap_or(0);
+            for entry in entries {
+                println!(
+                    "{:width$}  {}  {}",
+                    entry.id,
+                    entry.deleted,
+                    entry.path,
+                    width = width
+                );
+            }
+        }
+        TrashAction::Restore { note } => {
+            let id = trash.find(note)?.id.clone();
+            let restored = trash.restore(&id)?;
+            println!("Restored {}", restored.to_string_lossy());
+        }
+        TrashAction::Empty { force } => {
+            let count = trash.entries().len();
+            if !force {
+                bail!(
+                    "This permanently deletes {} {}, use --force to confirm",
+                    count,
+                    if count == 1 { "note" } else { "notes" }
+                );
+            }
+            let count = trash.empty()?;
+            println!(
+                "Permanently deleted {} {}",
+                count,
+                if count == 1 { "note" } else { "notes" }
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,1003 @@
+mod schema;
+
+use std::{
+    env,
+    fmt::{self, Display, Write as _},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use resolve_path::PathResolveExt;
+use serde::Deserialize;
+
+use crate::{args::Args, theme::Theme};
+
+use self::schema::{CreateSettings, HookSettings, OneOrMany, Settings};
+
+const CONFIG_FILE: &str = "jot/conf.toml";
+const CSS_FILE: &str = "jot.css";
+const LOCAL_CONFIG_FILE: &str = ".jot.toml";
+const DEFAULT_ROOT: &str = "~/notes";
+const DEFAULT_SUBDIR: &str = "atoms";
+const DEFAULT_ARCHIVE: &str = "archive";
+const DEFAULT_JUMP: bool = true;
+const DEFAULT_DISCARD_EMPTY: bool = true;
+const FALLBACK_EDITOR: &str = "vim";
+const DEFAULT_REMOTE: &str = "origin";
+const DEFAULT_REVIEW_DIR: &str = "reviews";
+const DEFAULT_TIMESTAMP_FORMAT: &str = "%FT%T%:z";
+
+/// Every resolved setting that can be inspected, in display order.
+pub const KEYS: &[&str] = &[
+    "notebook",
+    "root",
+    "subdir",
+    "archive",
+    "editor",
+    "jump",
+    "jump_args",
+    "discard_empty",
+    "theme",
+    "css",
+    "pdf.css",
+    "hooks.post_create",
+    "hooks.post_edit",
+    "hooks.pre_render",
+    "hooks.post_render",
+    "hooks.fatal",
+    "git.auto_commit",
+    "git.auto_sync",
+    "git.remote",
+    "review.dir",
+    "review.template",
+    "create.frontmatter",
+    "create.timezone",
+    "create.timestamp_format",
+];
+
+/// Configuration reader.
+pub struct Config {
+    /// The editor to use. Requires an editor string even if the no-edit flag is passed.
+    pub editor: String,
+
+    /// Attempt to jump to the last line of the file to edit. On by default but set to false in
+    /// config if it causes the editor to error.
+    pub jump: bool,
+
+    /// Template for the editor arguments that jump to a line, overriding the built-in ones
+    pub jump_args: Option<String>,
+
+    /// Delete new notes started without text that are still empty after editing
+    pub discard_empty: bool,
+
+    /// The root path of the notes directory
+    root: PathBuf,
+
+    /// The location inside the root to store notes
+    subdir: String,
+
+    /// The location inside the root to archive atoms to
+    archive: String,
+
+    /// The built-in theme to render with, underneath any stylesheets
+    pub theme: Theme,
+
+    /// The paths to the css files to inject into the header when rendering HTML, in order
+    pub css: Vec<PathBuf>,
+
+    /// The paths to the print css files, injected after the others when rendering PDFs
+    pub pdf_css: Vec<PathBuf>,
+
+    /// Commands run around creating, editing, and rendering notes
+    pub hooks: Hooks,
+
+    /// Committing and syncing the notes root with git
+    pub git: Git,
+
+    /// Writing periodic review notes
+    pub review: Review,
+
+    /// Creating new notes
+    pub create: Create,
+
+    /// The name of the notebook in use, if any
+    pub notebook: Option<String>,
+
+    /// Where each resolved setting came from
+    sources: Vec<(&'static str, Source)>,
+}
+
+/// Hook commands, each run through the shell.
+#[derive(Debug, Default)]
+pub struct Hooks {
+    pub post_create: Option<String>,
+    pub post_edit: Option<String>,
+    pub pre_render: Option<String>,
+    pub post_render: Option<String>,
+
+    /// Whether a failing hook stops jot with an error
+    pub fatal: bool,
+}
+
+/// Git settings for the notes root.
+#[derive(Debug)]
+pub struct Git {
+    /// Commit each note after it is created or edited
+    pub auto_commit: bool,
+
+    /// Pull and push after each automatic commit
+    pub auto_sync: bool,
+
+    /// The remote to pull from and push to
+    pub remote: String,
+}
+
+/// Settings for review notes.
+#[derive(Debug)]
+pub struct Review {
+    /// Where review notes are written inside the root
+    pub dir: String,
+
+    /// The template to write review notes from instead of the built-in one
+    pub template: Option<PathBuf>,
+}
+
+/// Settings for new notes.
+#[derive(Debug)]
+pub struct Create {
+    /// Front matter fields added to every new note, before any given with --meta
+    pub frontmatter: toml::Table,
+
+    /// The timezone new notes are dated in
+    pub timezone: Timezone,
+
+    /// The strftime format of the timestamp in the front matter
+    pub timestamp_format: String,
+}
+
+impl Create {
+    /// The timestamp for the front matter of a note created at the date.
+    pub fn timestamp(&self, date: &DateTime<FixedOffset>) -> Result<String> {
+        let mut timestamp = String::new();
+        write!(timestamp, "{}", date.format(&self.timestamp_format))
+            .map_err(|_| anyhow!("Invalid timestamp format '{}'", self.timestamp_format))?;
+
+        Ok(timestamp)
+    }
+
+    /// Read a timestamp from the front matter of a note, in the timestamp format or else as RFC
+    /// 3339. Timestamps without an offset are in the timezone, and those without a time are taken
+    /// to be at midnight.
+    pub fn parse_timestamp(&self, timestamp: &str) -> Option<DateTime<FixedOffset>> {
+        let timestamp = timestamp.trim();
+        let format = &self.timestamp_format;
+        DateTime::parse_from_str(timestamp, format)
+            .ok()
+            .or_else(|| {
+                let local = NaiveDateTime::parse_from_str(timestamp, format)
+                    .ok()
+                    .or_else(|| {
+                        NaiveDate::parse_from_str(timestamp, format)
+                            .ok()?
+                            .and_hms_opt(0, 0, 0)
+                    })?;
+                self.timezone.at(&local)
+            })
+            .or_else(|| DateTime::parse_from_rfc3339(timestamp).ok())
+    }
+}
+
+/// The timezone to date new notes in, either the system's or a named IANA timezone.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Timezone {
+    #[default]
+    Local,
+    Named(Tz),
+}
+
+impl Timezone {
+    /// Read a timezone from its IANA name, such as `Europe/Paris` or `UTC`, or `local`.
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.eq_ignore_ascii_case("local") {
+            true => Ok(Timezone::Local),
+            false => name
+                .parse::<Tz>()
+                .map(Timezone::Named)
+                .map_err(|_| anyhow!("Unknown timezone '{}'", name)),
+        }
+    }
+
+    /// The current time in the timezone.
+    pub fn now(&self) -> DateTime<FixedOffset> {
+        self.convert(Utc::now())
+    }
+
+    /// A time in another timezone as the same instant in this one.
+    pub fn convert<T: TimeZone>(&self, date: DateTime<T>) -> DateTime<FixedOffset> {
+        match self {
+            Timezone::Local => date.with_timezone(&Local).fixed_offset(),
+            Timezone::Named(tz) => date.with_timezone(tz).fixed_offset(),
+        }
+    }
+
+    /// A wall clock time in the timezone, taking the earlier time when the clocks go back. Times
+    /// skipped when the clocks go forward don't exist.
+    pub fn at(&self, date: &NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            Timezone::Local => Local
+                .from_local_datetime(date)
+                .earliest()
+                .map(|date| date.fixed_offset()),
+            Timezone::Named(tz) => tz
+                .from_local_datetime(date)
+                .earliest()
+                .map(|date| date.fixed_offset()),
+        }
+    }
+}
+
+impl Display for Timezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Timezone::Local => write!(f, "local"),
+            Timezone::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+/// Where a configuration value came from, in increasing order of precedence.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Default,
+    File(PathBuf),
+    Env(String),
+    Cli(&'static str),
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::File(path) => write!(f, "file: {}", path.to_string_lossy()),
+            Source::Env(var) => write!(f, "env: {}", var),
+            Source::Cli(flag) => write!(f, "cli: {}", flag),
+        }
+    }
+}
+
+/// A single layer of settings, such as the contents of a config file or the CLI arguments.
+struct Layer {
+    source: Source,
+    settings: Settings,
+}
+
+impl Config {
+    /// Build a new config from the global config file, merged with any local `.jot.toml`.
+    ///
+    /// Each setting is taken from the highest priority layer that provides it, with the
+    /// precedence: defaults < global file < local file < `JOT_*` env vars < CLI arguments.
+    /// Within each file, the selected notebook overrides the top level of the file. The `EDITOR`
+    /// env var is the exception, acting only as a fallback when no file sets an editor.
+    ///
+    /// The global file is the one passed as an argument or in `JOT_CONFIG`, falling back to the
+    /// default location. The local file is found by searching upwards from the current directory.
+    pub fn try_new(args: &Args) -> Result<Self> {
+        let global = match Self::config_arg(args) {
+            Some(path) => Some(path.try_resolve()?.to_path_buf()),
+            None => Self::default_path()?,
+        };
+        let local = Self::local_path();
+
+        // The editor env var is only a fallback for when no file sets an editor
+        let mut layers = vec![Layer {
+            source: Source::Env("EDITOR".to_string()),
+            settings: Settings {
+                editor: env::var("EDITOR").ok(),
+                ..Default::default()
+            },
+        }];
+
+        if let Some(path) = &global {
+            layers.push(Layer {
+                source: Source::File(path.clone()),
+                settings: Self::read_settings(path)?,
+            });
+        }
+        if let Some(path) = &local {
+            let dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+            let mut settings = Self::read_settings(path)?;
+            Self::anchor_local_paths(&mut settings, &dir);
+
+            // The local file is the notebook unless it chooses one itself, so don't let a global
+            // default notebook take over from it
+            if settings.notebook.is_none() {
+                layers
+                    .iter_mut()
+                    .for_each(|layer| layer.settings.notebook = None);
+            }
+
+            layers.push(Layer {
+                source: Source::File(path.clone()),
+                settings,
+            });
+        }
+
+        layers.extend(Self::env_layers(|name| env::var(name).ok())?);
+        layers.push(Layer {
+            source: Source::Cli("--notebook"),
+            settings: Settings {
+                notebook: args.notebook.clone(),
+                ..Default::default()
+            },
+        });
+
+        // Choose the notebook first, then slot each file's notebook section in directly above it
+        let notebook = resolve(&layers, |s| s.notebook.as_ref()).map(|(name, _)| name.clone());
+        if let Some(name) = &notebook {
+            layers = Self::with_notebook(layers, name)?;
+        }
+
+        layers.push(Layer {
+            source: Source::Cli("--timezone"),
+            settings: Settings {
+                create: args.timezone.clone().map(|timezone| CreateSettings {
+                    timezone: Some(timezone),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        });
+        layers.push(Layer {
+            source: Source::Cli("--utc"),
+            settings: Settings {
+                create: args.utc.then(|| CreateSettings {
+                    timezone: Some("UTC".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        });
+        layers.push(Layer {
+            source: Source::Cli("--subdir"),
+            settings: Settings {
+                subdir: args.subdir.clone(),
+                ..Default::default()
+            },
+        });
+
+        Self::from_layers(&layers, notebook)
+    }
+
+    /// Build a Config from the provided layers, with later layers taking priority and defaults
+    /// filling in any setting that no layer provides.
+    fn from_layers(layers: &[Layer], notebook: Option<String>) -> Result<Self> {
+        let mut sources = Vec::new();
+
+        let editor = pick(layers, &mut sources, "editor", |s| s.editor.as_ref())
+            .cloned()
+            .unwrap_or(FALLBACK_EDITOR.to_string());
+
+        let jump = pick(layers, &mut sources, "jump", |s| s.jump.as_ref())
+            .copied()
+            .unwrap_or(DEFAULT_JUMP);
+
+        let jump_args = pick(layers, &mut sources, "jump_args", |s| s.jump_args.as_ref()).cloned();
+
+        let discard_empty = pick(layers, &mut sources, "discard_empty", |s| {
+            s.discard_empty.as_ref()
+        })
+        .copied()
+        .unwrap_or(DEFAULT_DISCARD_EMPTY);
+
+        let root = pick(layers, &mut sources, "root", |s| s.root.as_ref())
+            .map_or(DEFAULT_ROOT, String::as_str);
+        let root = PathBuf::from(root).try_resolve()?.to_path_buf();
+
+        let subdir = pick(layers, &mut sources, "subdir", |s| s.subdir.as_ref())
+            .cloned()
+            .unwrap_or(DEFAULT_SUBDIR.to_string());
+
+        let archive = pick(layers, &mut sources, "archive", |s| s.archive.as_ref())
+            .cloned()
+            .unwrap_or(DEFAULT_ARCHIVE.to_string());
+
+        pick(layers, &mut sources, "notebook", |s| s.notebook.as_ref());
+
+        let theme = pick(layers, &mut sources, "theme", |s| s.theme.as_ref())
+            .copied()
+            .unwrap_or_default();
+
+        // Use the configured css files if there are any, otherwise first attempt to find a css
+        // file in the root of the notes directory, and if that doesn't exist, try next to each
+        // config file, local first
+        let css = match pick(layers, &mut sources, "css", |s| s.css.as_ref()) {
+            Some(css) => resolve_paths(css)?,
+            None => root
+                .join(CSS_FILE)
+                .canonicalize()
+                .ok()
+                .or_else(|| {
+                    layers.iter().rev().find_map(|layer| match &layer.source {
+                        Source::File(path) => path
+                            .parent()
+                            .and_then(|dir| dir.join(CSS_FILE).canonicalize().ok()),
+                        _ => None,
+                    })
+                })
+                .into_iter()
+                .collect(),
+        };
+
+        let pdf_css = pick(layers, &mut sources, "pdf.css", |s| {
+            s.pdf.as_ref()?.css.as_ref()
+        })
+        .map(resolve_paths)
+        .transpose()?
+        .unwrap_or_default();
+
+        let mut hook = |key, get: fn(&HookSettings) -> Option<&String>| {
+            pick(layers, &mut sources, key, |s| get(s.hooks.as_ref()?)).cloned()
+        };
+        let hooks = Hooks {
+            post_create: hook("hooks.post_create", |h| h.post_create.as_ref()),
+            post_edit: hook("hooks.post_edit", |h| h.post_edit.as_ref()),
+            pre_render: hook("hooks.pre_render", |h| h.pre_render.as_ref()),
+            post_render: hook("hooks.post_render", |h| h.post_render.as_ref()),
+            fatal: pick(layers, &mut sources, "hooks.fatal", |s| {
+                s.hooks.as_ref()?.fatal.as_ref()
+            })
+            .copied()
+            .unwrap_or_default(),
+        };
+
+        let git = Git {
+            auto_commit: pick(layers, &mut sources, "git.auto_commit", |s| {
+                s.git.as_ref()?.auto_commit.as_ref()
+            })
+            .copied()
+            .unwrap_or_default(),
+            auto_sync: pick(layers, &mut sources, "git.auto_sync", |s| {
+                s.git.as_ref()?.auto_sync.as_ref()
+            })
+            .copied()
+            .unwrap_or_default(),
+            remote: pick(layers, &mut sources, "git.remote", |s| {
+                s.git.as_ref()?.remote.as_ref()
+            })
+            .cloned()
+            .unwrap_or(DEFAULT_REMOTE.to_string()),
+        };
+
+        let review = Review {
+            dir: pick(layers, &mut sources, "review.dir", |s| {
+                s.review.as_ref()?.dir.as_ref()
+            })
+            .cloned()
+            .unwrap_or(DEFAULT_REVIEW_DIR.to_string()),
+            template: pick(layers, &mut sources, "review.template", |s| {
+                s.review.as_ref()?.template.as_ref()
+            })
+            .map(|path| {
+                PathBuf::from(path)
+                    .try_resolve()
+                    .map(|path| path.to_path_buf())
+            })
+            .transpose()?,
+        };
+
+        let create = Create {
+            frontmatter: pick(layers, &mut sources, "create.frontmatter", |s| {
+                s.create.as_ref()?.frontmatter.as_ref()
+            })
+            .cloned()
+            .unwrap_or_default(),
+            timezone: pick(layers, &mut sources, "create.timezone", |s| {
+                s.create.as_ref()?.timezone.as_ref()
+            })
+            .map(|name| Timezone::parse(name))
+            .transpose()?
+            .unwrap_or_default(),
+            timestamp_format: pick(layers, &mut sources, "create.timestamp_format", |s| {
+                s.create.as_ref()?.timestamp_format.as_ref()
+            })
+            .cloned()
+            .unwrap_or(DEFAULT_TIMESTAMP_FORMAT.to_string()),
+        };
+
+        Ok(Self {
+            editor,
+            jump,
+            jump_args,
+            discard_empty,
+            root,
+            subdir,
+            archive,
+            theme,
+            css,
+            pdf_css,
+            hooks,
+            git,
+            review,
+            create,
+            notebook,
+            sources,
+        })
+    }
+
+    /// The path to the global config file, whether or not it exists.
+    ///
+    /// This is the file passed as an argument or in `JOT_CONFIG`, or the default location otherwise.
+    pub fn global_path(args: &Args) -> Result<PathBuf> {
+        match Self::config_arg(args) {
+            Some(path) => Ok(path.try_resolve()?.to_path_buf()),
+            None => Ok(dirs::config_dir()
+                .ok_or(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "Config dir could not be resolved",
+                ))?
+                .join(CONFIG_FILE)),
+        }
+    }
+
+    /// The config file path given by the config argument, or the `JOT_CONFIG` env var.
+    fn config_arg(args: &Args) -> Option<PathBuf> {
+        args.config
+            .clone()
+            .or_else(|| env::var_os("JOT_CONFIG").map(PathBuf::from))
+    }
+
+    /// Settings provided by `JOT_*` env vars, in a layer per var so each value can be attributed.
+    ///
+    /// Every setting in [`KEYS`] has a var named after its key, such as `JOT_SUBDIR` for `subdir`
+    /// and `JOT_CREATE_TIMEZONE` for `create.timezone`, read by the given lookup.
+    fn env_layers(var: impl Fn(&str) -> Option<String>) -> Result<Vec<Layer>> {
+        KEYS.iter()
+            .filter_map(|key| {
+                let name = env_var(key);
+                let value = var(&name).filter(|value| !value.is_empty())?;
+                Some(env_setting(key, &name, &value).map(|settings| Layer {
+                    source: Source::Env(name),
+                    settings,
+                }))
+            })
+            .collect()
+    }
+
+    /// The path to the nearest local config file, if there is one.
+    ///
+    /// Searches from the current directory upwards, in the same way git finds its `.git`
+    /// directory.
+    pub fn local_path() -> Option<PathBuf> {
+        env::current_dir().ok().and_then(|start| {
+            start
+                .ancestors()
+                .map(|dir| dir.join(LOCAL_CONFIG_FILE))
+                .find(|path| path.is_file())
+        })
+    }
+
+    /// The path a new local config file is created at in the current directory.
+    pub fn new_local_path() -> Result<PathBuf> {
+        Ok(env::current_dir()?.join(LOCAL_CONFIG_FILE))
+    }
+
+    /// Find the default config location.
+    /// Will choose the system specific location for the config file based on XDG conventions.
+    /// If the file errors for not being present, this is OK - we return None to build a default
+    /// config. Any other error is passed back to the caller.
+    fn default_path() -> Result<Option<PathBuf>> {
+        let config_path = dirs::config_dir()
+            .ok_or(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Config dir could not be resolved",
+            ))?
+            .join(CONFIG_FILE)
+            .canonicalize();
+
+        match config_path {
+            Ok(config_path) => Ok(Some(config_path)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Read the settings from a config file, with relative css and template paths taken to be
+    /// relative to the config file.
+    fn read_settings(path: &Path) -> Result<Settings> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read config file {}", path.to_string_lossy()))?;
+        let mut settings = Settings::parse(&content, path)?;
+
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let anchor = |settings: &mut Settings| {
+            let pdf_css = settings.pdf.as_mut().and_then(|pdf| pdf.css.as_mut());
+            let paths = settings
+                .css
+                .iter_mut()
+                .chain(pdf_css)
+                .flat_map(OneOrMany::iter_mut);
+            let template = settings
+                .review
+                .as_mut()
+                .and_then(|review| review.template.as_mut());
+            for path in paths.chain(template) {
+                if Path::new(path).is_relative() && !path.starts_with('~') {
+                    *path = dir.join(&path).to_string_lossy().into_owned();
+                }
+            }
+        };
+        anchor(&mut settings);
+        settings.notebooks.values_mut().for_each(anchor);
+
+        Ok(settings)
+    }
+
+    /// Check that the content of a config file is valid, without building a config from it.
+    pub fn validate(content: &str, path: &Path) -> Result<()> {
+        Settings::parse(content, path).map(|_| ())
+    }
+
+    /// Make relative paths in a local config file relative to the file rather than the current
+    /// directory. A local file without a root treats its own directory as the notes root.
+    fn anchor_local_paths(settings: &mut Settings, dir: &Path) {
+        let anchor = |settings: &mut Settings| {
+            if let Some(root) = settings.root.as_mut() {
+                let path = Path::new(root);
+                if path.is_relative() && !root.starts_with('~') {
+                    *root = dir.join(path).to_string_lossy().into_owned();
+                }
+            }
+        };
+
+        anchor(settings);
+        settings.notebooks.values_mut().for_each(anchor);
+
+        if settings.root.is_none() {
+            settings.root = Some(dir.to_string_lossy().into_owned());
+        }
+    }
+
+    /// Insert the named notebook's section from each file layer directly above that file.
+    ///
+    /// Errors if no file defines the notebook.
+    fn with_notebook(layers: Vec<Layer>, name: &str) -> Result<Vec<Layer>> {
+        let mut found = false;
+        let mut expanded = Vec::with_capacity(layers.len());
+
+        for layer in layers {
+            let section = layer.settings.notebooks.get(name).cloned();
+            let source = layer.source.clone();
+            expanded.push(layer);
+
+            if let Some(settings) = section {
+                found = true;
+                expanded.push(Layer { source, settings });
+            }
+        }
+
+        if !found {
+            bail!("Notebook '{}' is not defined in the config", name);
+        }
+
+        Ok(expanded)
+    }
+
+    pub fn base_dir(&self) -> PathBuf {
+        self.root.join(&self.subdir)
+    }
+
+    /// Where an atom created at the date goes, in a folder for its year and month in the base
+    /// notes folder, named with its atom ID.
+    pub fn atom_path(&self, date: &DateTime<FixedOffset>) -> PathBuf {
+        self.base_dir()
+            .join(date.format("%Y/%m").to_string())
+            .join(format!("{}.md", date.format("%Y%m%d_%H%M%S")))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The directory atoms are archived to, mirroring the layout of the atoms subdirectory.
+    pub fn archive_dir(&self) -> PathBuf {
+        self.root.join(&self.archive)
+    }
+
+    /// The resolved value of a single setting formatted as TOML, along with where it came from.
+    pub fn get(&self, key: &str) -> Option<(toml::Value, Source)> {
+        let value = match key {
+            "editor" => self.editor.clone().into(),
+            "jump" => self.jump.into(),
+            "jump_args" => self.jump_args.clone()?.into(),
+            "discard_empty" => self.discard_empty.into(),
+            "root" => self.root.to_string_lossy().into_owned().into(),
+            "subdir" => self.subdir.clone().into(),
+            "archive" => self.archive.clone().into(),
+            "notebook" => self.notebook.clone()?.into(),
+            "theme" => self.theme.to_string().into(),
+            "css" => paths_value(&self.css),
+            "pdf.css" => paths_value(&self.pdf_css),
+            "hooks.post_create" => self.hooks.post_create.clone()?.into(),
+            "hooks.post_edit" => self.hooks.post_edit.clone()?.into(),
+            "hooks.pre_render" => self.hooks.pre_render.clone()?.into(),
+            "hooks.post_render" => self.hooks.post_render.clone()?.into(),
+            "hooks.fatal" => self.hooks.fatal.into(),
+            "git.auto_commit" => self.git.auto_commit.into(),
+            "git.auto_sync" => self.git.auto_sync.into(),
+            "git.remote" => self.git.remote.clone().into(),
+            "review.dir" => self.review.dir.clone().into(),
+            "review.template" => self
+                .review
+                .template
+                .as_ref()?
+                .to_string_lossy()
+                .into_owned()
+                .into(),
+            "create.frontmatter" => match self.create.frontmatter.is_empty() {
+                true => return None,
+                false => self.create.frontmatter.clone().into(),
+            },
+            "create.timezone" => self.create.timezone.to_string().into(),
+            "create.timestamp_format" => self.create.timestamp_format.clone().into(),
+            _ => return None,
+        };
+        let source = self
+            .sources
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, source)| source.clone())
+            .unwrap_or(Source::Default);
+
+        Some((value, source))
+    }
+
+    /// All resolved settings, in display order.
+    pub fn entries(&self) -> Vec<(&'static str, toml::Value, Source)> {
+        KEYS.iter()
+            .filter_map(|&key| self.get(key).map(|(value, source)| (key, value, source)))
+            .collect()
+    }
+
+    /// A commented config file containing the default settings.
+    pub fn template() -> String {
+        format!(
+            r#"# Jot configuration
+#
+# Every setting is optional. Settings can be overridden per notebook in a [notebooks.<name>]
+# table, and per project in a .jot.toml file found in the current directory or above.
+
+# The notes root directory
+root = "{}"
+
+# Where new atoms are stored inside the root
+subdir = "{}"
+
+# Where jot archive moves atoms to inside the root
+# archive = "{}"
+
+# Open the editor at the end of the note
+jump = {}
+
+# The editor to open notes with, falls back to $EDITOR then {}. Can include arguments, and
+# {{path}} and {{line}} placeholders to control where the path and line number go
+# editor = "{}"
+
+# Arguments to jump to a line, for editors jot doesn't already know how to jump in
+# jump_args = "--line {{line}} {{path}}"
+
+# The built-in theme for HTML and PDF output: default, serif, dark, or none
+# theme = "default"
+
+# Stylesheets layered over the theme, either a path or a list of paths. Otherwise jot.css is
+# used from the notes root or next to this file
+# css = ["~/notes/style.css", "~/notes/tables.css"]
+
+# Print stylesheets added after the others when rendering PDFs
+# [pdf]
+# css = "~/notes/print.css"
+
+# Delete new notes started without text that are still empty after editing, keep them with --keep
+discard_empty = {}
+
+# Shell commands run around creating, editing, and rendering notes. Each receives the note path as
+# $1 and any output path as $2, and the environment has JOT_HOOK, JOT_NOTE, JOT_OUTPUT, JOT_ROOT,
+# JOT_NOTEBOOK, and JOT_META_<KEY> for each front matter field. Failures are reported, and stop
+# jot when fatal is set
+# [hooks]
+# post_create = "git -C \"$JOT_ROOT\" add \"$1\""
+# post_edit = "rsync -a \"$JOT_ROOT/\" /mnt/backup/notes"
+# pre_render = ""
+# post_render = "notify-send Rendered \"$2\""
+# fatal = false
+
+# Commit notes to git as they are created or edited when the root is in a repository, and pull and
+# push after each commit with auto_sync. Commit and sync by hand with jot commit and jot sync
+# [git]
+# auto_commit = true
+# auto_sync = false
+# remote = "{}"
+
+# Where jot review writes review notes inside the root, and a template to write them from instead
+# of the built-in one, relative to this file. Templates fill in {{{{period}}}}, {{{{start}}}}, {{{{end}}}},
+# {{{{count}}}}, {{{{atoms}}}}, and {{{{tasks}}}}
+# [review]
+# dir = "{}"
+# template = "review.md"
+
+# Front matter fields added to every new note after the built-in ones, and before any given with
+# --meta. Text can include strftime codes such as %Y for the time the note is created, written %%
+# for a literal %, and ${{VAR}} for environment variables
+# [create.frontmatter]
+# author = "${{USER}}"
+# project = "jot"
+# week = "%G-W%V"
+
+# The timezone new notes are dated in, as an IANA name such as "Europe/Paris" or "UTC", or local
+# for the system timezone, and the strftime format of their timestamp. Override the timezone with
+# --timezone or --utc
+# [create]
+# timezone = "local"
+# timestamp_format = "{}"
+
+# The notebook to use when none is selected with --notebook or JOT_NOTEBOOK
+# notebook = "personal"
+
+# [notebooks.personal]
+# root = "~/notes"
+
+# [notebooks.work]
+# root = "~/work/notes"
+"#,
+            DEFAULT_ROOT,
+            DEFAULT_SUBDIR,
+            DEFAULT_ARCHIVE,
+            DEFAULT_JUMP,
+            FALLBACK_EDITOR,
+            FALLBACK_EDITOR,
+            DEFAULT_DISCARD_EMPTY,
+            DEFAULT_REMOTE,
+            DEFAULT_REVIEW_DIR,
+            DEFAULT_TIMESTAMP_FORMAT
+        )
+    }
+}
+
+/// Find the highest priority layer providing a setting, using the getter to pull the setting out
+/// of each layer.
+fn resolve<'a, T, F>(layers: &'a [Layer], get: F) -> Option<(&'a T, &'a Source)>
+where
+    F: Fn(&'a Settings) -> Option<&'a T>,
+{
+    layers
+        .iter()
+        .rev()
+        .find_map(|layer| get(&layer.settings).map(|value| (value, &layer.source)))
+}
+
+/// Resolve a setting from the layers as with [`resolve`], recording the layer it came from.
+fn pick<'a, T, F>(
+    layers: &'a [Layer],
+    sources: &mut Vec<(&'static str, Source)>,
+    key: &'static str,
+    get: F,
+) -> Option<&'a T>
+where
+    F: Fn(&'a Settings) -> Option<&'a T>,
+{
+    let (value, source) = resolve(layers, get)?;
+    sources.push((key, source.clone()));
+    Some(value)
+}
+
+/// The name of the env var for a setting, such as `JOT_PDF_CSS` for `pdf.css`.
+fn env_var(key: &str) -> String {
+    format!("JOT_{}", key.replace('.', "_").to_uppercase())
+}
+
+/// The settings given by the env var for a setting key.
+///
+/// The value is read as TOML where it fits the setting, such as `true` or `{ a = 1 }`, and as a
+/// plain string otherwise. Booleans also accept the usual spellings such as `yes` and `0`, and
+/// stylesheets take a list of paths separated as in `PATH`.
+fn env_setting(key: &str, name: &str, value: &str) -> Result<Settings> {
+    let mut candidates = Vec::new();
+    if key == "css" || key == "pdf.css" {
+        let paths = env::split_paths(value)
+            .map(|path| path.to_string_lossy().into_owned().into())
+            .collect();
+        candidates.push(toml::Value::Array(paths));
+    }
+    candidates.extend(toml::Value::deserialize(toml::de::ValueDeserializer::new(
+        value,
+    )));
+    match value.to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => candidates.push(true.into()),
+        "0" | "false" | "no" | "off" => candidates.push(false.into()),
+        _ => {}
+    }
+
+    // Nest the value in the tables for each section of the key
+    let settings = |value| {
+        let table = key.rsplit('.').fold(value, |value, segment| {
+            toml::Value::Table(toml::Table::from_iter([(segment.to_string(), value)]))
+        });
+        Settings::deserialize(table)
+    };
+    if let Some(settings) = candidates
+        .into_iter()
+        .find_map(|value| settings(value).ok())
+    {
+        return Ok(settings);
+    }
+
+    settings(value.into()).with_context(|| format!("Invalid value for {} '{}'", name, value))
+}
+
+/// Resolve each path in a path setting, expanding `~`.
+fn resolve_paths(paths: &OneOrMany) -> Result<Vec<PathBuf>> {
+    paths
+        .to_vec()
+        .into_iter()
+        .map(|path| Ok(PathBuf::from(path).try_resolve()?.to_path_buf()))
+        .collect()
+}
+
+/// A list of paths formatted as a TOML array.
+fn paths_value(paths: &[PathBuf]) -> toml::Value {
+    toml::Value::Array(
+        paths
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned().into())
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_setting_has_an_env_var() {
+        let value = |key: &str| match key {
+            "jump" | "hooks.fatal" | "git.auto_sync" => "yes",
+            "discard_empty" | "git.auto_commit" => "false",
+            "theme" => "dark",
+            "create.frontmatter" => "{ author = 'me' }",
+            "create.timezone" => "Asia/Tokyo",
+            "create.timestamp_format" => "%F",
+            "css" | "pdf.css" => "a.css",
+            _ => "env",
+        };
+        let layers = Config::env_layers(|name| {
+            KEYS.iter()
+                .find(|key| env_var(key) == name)
+                .map(|key| value(key).to_string())
+        })
+        .unwrap();
+        let notebook = resolve(&layers, |s| s.notebook.as_ref()).map(|(name, _)| name.clone());
+        let config = Config::from_layers(&layers, notebook).unwrap();
+
+        for key in KEYS {
+            let (_, source) = config.get(key).unwrap();
+            assert_eq!(source.to_string(), format!("env: {}", env_var(key)));
+        }
+        assert_eq!(env_var("hooks.post_create"), "JOT_HOOKS_POST_CREATE");
+        assert!(config.jump && !config.discard_empty && config.git.auto_sync);
+        assert_eq!(config.theme, Theme::Dark);
+        assert_eq!(config.create.frontmatter["author"].as_str(), Some("me"));
+    }
+
+    #[test]
+    fn reports_invalid_env_values() {
+        let err = env_setting("jump", "JOT_JUMP", "maybe").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid value for JOT_JUMP 'maybe'");
+        assert!(env_setting("subdir", "JOT_SUBDIR", "2024")
+            .unwrap()
+            .subdir
+            .is_some());
+        assert!(env_setting("create.timezone", "JOT_CREATE_TIMEZONE", "UTC").is_ok());
+    }
+}
@@ -0,0 +1,191 @@
+//! Typed schema for config files.
+//!
+//! Every setting is optional so that config files, notebooks, environment variables, and arguments
+//! can all be layered over one another, with defaults only applied once every layer is resolved.
+//! Unknown keys are rejected so that typos are reported rather than silently ignored. To add a new
+//! section such as `[pdf]`, add a field holding a struct for that section with the same derives.
+
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{anyhow, bail, Result};
+use serde::Deserialize;
+
+use crate::theme::Theme;
+
+/// The settings in a config file, or in a single notebook within one.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Settings {
+    /// The root path of the notes directory.
+    pub root: Option<String>,
+
+    /// The location inside the root to store notes.
+    pub subdir: Option<String>,
+
+    /// The location inside the root to archive atoms to.
+    pub archive: Option<String>,
+
+    /// The editor to open notes with.
+    pub editor: Option<String>,
+
+    /// Attempt to jump to the last line of the file when editing.
+    pub jump: Option<bool>,
+
+    /// Template for the editor arguments that jump to a line, using `{path}` and `{line}`.
+    pub jump_args: Option<String>,
+
+    /// Delete new notes started without text that are still empty after editing.
+    pub discard_empty: Option<bool>,
+
+    /// Stylesheets to inject into the header when rendering HTML, in order.
+    pub css: Option<OneOrMany>,
+
+    /// The built-in theme to render HTML and PDFs with, underneath any stylesheets.
+    pub theme: Option<Theme>,
+
+    /// Settings only used when rendering PDFs.
+    pub pdf: Option<PdfSettings>,
+
+    /// Commands run around creating, editing, and rendering notes.
+    pub hooks: Option<HookSettings>,
+
+    /// Committing and syncing the notes root with git.
+    pub git: Option<GitSettings>,
+
+    /// Writing periodic review notes.
+    pub review: Option<ReviewSettings>,
+
+    /// Creating new notes.
+    pub create: Option<CreateSettings>,
+
+    /// The default notebook. Only valid at the top level of a file.
+    pub notebook: Option<String>,
+
+    /// Named notebooks overriding the top level settings. Only valid at the top level of a file.
+    #[serde(default)]
+    pub notebooks: BTreeMap<String, Settings>,
+}
+
+/// Settings in the `[pdf]` section.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PdfSettings {
+    /// Print stylesheets, injected after the general stylesheets when rendering PDFs.
+    pub css: Option<OneOrMany>,
+}
+
+/// Settings in the `[hooks]` section.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct HookSettings {
+    /// Run after a new note is written, before it is opened for editing.
+    pub post_create: Option<String>,
+
+    /// Run after the editor exits.
+    pub post_edit: Option<String>,
+
+    /// Run before a note is rendered to HTML or PDF.
+    pub pre_render: Option<String>,
+
+    /// Run after a note is rendered to HTML or PDF.
+    pub post_render: Option<String>,
+
+    /// Stop jot with an error when a hook fails, rather than only reporting the failure.
+    pub fatal: Option<bool>,
+}
+
+/// Settings in the `[git]` section.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct GitSettings {
+    /// Commit a note after it is created or edited.
+    pub auto_commit: Option<bool>,
+
+    /// Pull and push after each automatic commit.
+    pub auto_sync: Option<bool>,
+
+    /// The remote to pull from and push to.
+    pub remote: Option<String>,
+}
+
+/// Settings in the `[review]` section.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ReviewSettings {
+    /// Where review notes are written inside the root.
+    pub dir: Option<String>,
+
+    /// The template review notes are written from.
+    pub template: Option<String>,
+}
+
+/// Settings in the `[create]` section.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CreateSettings {
+    /// Front matter fields added to every new note, in order.
+    pub frontmatter: Option<toml::Table>,
+
+    /// The IANA timezone new notes are dated in, or `local` for the system timezone.
+    pub timezone: Option<String>,
+
+    /// The strftime format of the timestamp in the front matter of new notes.
+    pub timestamp_format: Option<String>,
+}
+
+/// A setting that can be given as either a single string or a list of strings.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged, expecting = "a string or a list of strings")]
+pub(crate) enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        match self {
+            OneOrMany::One(one) => std::slice::from_mut(one).iter_mut(),
+            OneOrMany::Many(many) => many.iter_mut(),
+        }
+    }
+
+    pub fn to_vec(&self) -> Vec<String> {
+        match self {
+            OneOrMany::One(one) => vec![one.clone()],
+            OneOrMany::Many(many) => many.clone(),
+        }
+    }
+}
+
+impl Settings {
+    /// Parse the content of the config file at the given path.
+    ///
+    /// Errors identify the file along with the line, key, and the problem with its value.
+    pub fn parse(content: &str, path: &Path) -> Result<Self> {
+        let deserializer = toml::Deserializer::new(content);
+        let settings =
+            serde_path_to_error::deserialize::<_, Self>(deserializer).map_err(|err| {
+                let context = match err.path().to_string().as_str() {
+                    "." => format!("Invalid config file {}", path.to_string_lossy()),
+                    key => format!(
+                        "Invalid config file {} at key `{}`",
+                        path.to_string_lossy(),
+                        key
+                    ),
+                };
+                anyhow!(err.into_inner()).context(context)
+            })?;
+
+        for (name, notebook) in &settings.notebooks {
+            if notebook.notebook.is_some() || !notebook.notebooks.is_empty() {
+                bail!(
+                    "Invalid config file {}: notebook '{}' cannot contain notebook or notebooks keys",
+                    path.to_string_lossy(),
+                    name
+                );
+            }
+        }
+
+        Ok(settings)
+    }
+}
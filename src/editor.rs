@@ -0,0 +1,218 @@
+//! Launching the configured editor on a note.
+//!
+//! The editor setting is split into words like a shell would, so it can carry arguments such as
+//! `code --wait`. It can also be a full template using `{path}` and `{line}` placeholders, e.g.
+//! `hx {path}:{line}`. Otherwise the path is appended, along with arguments to jump to the end of
+//! the note for editors that are known to support it, or as given in the jump_args setting.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    fs,
+    path::Path,
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::config::Config;
+
+/// Jump argument templates for well known editors, keyed by program name.
+const JUMP_TEMPLATES: &[(&[&str], &str)] = &[
+    (
+        &[
+            "vi",
+            "vim",
+            "nvim",
+            "gvim",
+            "mvim",
+            "nano",
+            "pico",
+            "emacs",
+            "emacsclient",
+            "kak",
+            "micro",
+            "joe",
+            "mg",
+            "gedit",
+        ],
+        "+{line} {path}",
+    ),
+    (
+        &["code", "code-insiders", "codium", "cursor"],
+        "--goto {path}:{line}",
+    ),
+    (
+        &["subl", "sublime_text", "hx", "helix", "zed"],
+        "{path}:{line}",
+    ),
+];
+
+/// The editor has run but failed, carrying its exit code so jot can exit with the same code.
+#[derive(Debug)]
+pub struct EditorError {
+    pub program: String,
+    pub code: Option<i32>,
+}
+
+impl Display for EditorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "Editor {} exited with status {}", self.program, code),
+            None => write!(f, "Editor {} was terminated by a signal", self.program),
+        }
+    }
+}
+
+impl Error for EditorError {}
+
+pub struct Editor<'a> {
+    words: Vec<String>,
+    jump: bool,
+    jump_args: Option<&'a str>,
+}
+
+impl<'a> Editor<'a> {
+    /// Parse the editor command from the config.
+    pub fn new(config: &'a Config) -> Result<Self> {
+        let words = shell_words::split(&config.editor)
+            .with_context(|| format!("Unable to parse editor command '{}'", config.editor))?;
+        if words.is_empty() {
+            bail!("The editor command is empty");
+        }
+
+        Ok(Self {
+            words,
+            jump: config.jump,
+            jump_args: config.jump_args.as_deref(),
+        })
+    }
+
+    /// Open the note at the given path, jumping to its last line if configured, and wait for the
+    /// editor to exit. Errors with an [`EditorError`] if the editor fails.
+    pub fn open(&self, path: &Path) -> Result<()> {
+        let line = match self.jump {
+            true => Some(fs::read_to_string(path)?.lines().count().max(1)),
+            false => None,
+        };
+
+        let mut cmd = self.command(path, line)?;
+        let program = self.words[0].clone();
+        let status = cmd
+            .status()
+            .with_context(|| format!("Unable to launch editor {}", program))?;
+
+        match status.success() {
+            true => Ok(()),
+            false => Err(EditorError {
+                program,
+                code: status.code(),
+            }
+            .into()),
+        }
+    }
+
+    /// Build the command to open the path, optionally at the given line.
+    fn command(&self, path: &Path, line: Option<usize>) -> Result<Command> {
+        let path = path.to_string_lossy();
+        let fill = |word: &str| {
+            word.replace("{path}", &path)
+                .replace("{line}", &line.unwrap_or(1).to_string())
+        };
+
+        let mut words = self.words.clone();
+        let is_template = words.iter().any(|word| word.contains("{path}"));
+        if !is_template {
+            let template = match line {
+                Some(_) => self.jump_template(),
+                None => None,
+            };
+            if let Some(template) = template {
+                words.extend(shell_words::split(template)?);
+            }
+            // Jump templates only need to say where the line goes, the path is added if missing
+            if !words.iter().any(|word| word.contains("{path}")) {
+                words.push("{path}".to_string());
+            }
+        }
+
+        let mut cmd = Command::new(fill(&words[0]));
+        cmd.args(words[1..].iter().map(|word| fill(word)));
+        Ok(cmd)
+    }
+
+    /// The jump template from the config, falling back to one for well known editors.
+    fn jump_template(&self) -> Option<&'a str> {
+        if self.jump_args.is_some() {
+            return self.jump_args;
+        }
+
+        let program = Path::new(&self.words[0])
+            .file_stem()?
+            .to_string_lossy()
+            .to_lowercase();
+        JUMP_TEMPLATES
+            .iter()
+            .find(|(programs, _)| programs.contains(&program.as_str()))
+            .map(|(_, template)| *template)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The program and arguments to open `note.md` at line 7 with the editor.
+    fn command(editor: &str, jump_args: Option<&str>, jump: bool) -> Vec<String> {
+        let editor = Editor {
+            words: shell_words::split(editor).unwrap(),
+            jump,
+            jump_args,
+        };
+        let cmd = editor
+            .command(Path::new("note.md"), jump.then_some(7))
+            .unwrap();
+        std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|word| word.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn jumps_in_well_known_editors() {
+        assert_eq!(command("vim", None, true), ["vim", "+7", "note.md"]);
+        assert_eq!(
+            command("/usr/bin/nvim", None, true),
+            ["/usr/bin/nvim", "+7", "note.md"]
+        );
+        assert_eq!(
+            command("code --wait", None, true),
+            ["code", "--wait", "--goto", "note.md:7"]
+        );
+        assert_eq!(command("hx", None, true), ["hx", "note.md:7"]);
+        assert_eq!(command("Subl.exe", None, true), ["Subl.exe", "note.md:7"]);
+    }
+
+    #[test]
+    fn only_appends_the_path_otherwise() {
+        assert_eq!(command("vim", None, false), ["vim", "note.md"]);
+        assert_eq!(command("ed -s", None, true), ["ed", "-s", "note.md"]);
+    }
+
+    #[test]
+    fn fills_in_templates() {
+        assert_eq!(
+            command("my-editor --line {line} {path}", None, true),
+            ["my-editor", "--line", "7", "note.md"]
+        );
+        assert_eq!(command("kate {path}", None, true), ["kate", "note.md"]);
+        assert_eq!(
+            command("ed", Some("--at {line}"), true),
+            ["ed", "--at", "7", "note.md"]
+        );
+        assert_eq!(
+            command("vim", Some("-c {line} {path}"), true),
+            ["vim", "-c", "7", "note.md"]
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Writing EPUB books, with a chapter for each note.
+
+use std::{
+    fs,
+    io::{Seek, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::html::escape;
+
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+<rootfiles>
+<rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+</rootfiles>
+</container>
+"#;
+
+/// An EPUB book being built up a chapter at a time.
+pub struct Epub {
+    title: String,
+    css: String,
+
+    /// The title and XHTML of each chapter, in order
+    chapters: Vec<(String, String)>,
+
+    /// The images included in the book, in order
+    images: Vec<Image>,
+}
+
+struct Image {
+    /// Where the image was read from
+    path: PathBuf,
+    href: String,
+    media_type: &'static str,
+    data: Vec<u8>,
+}
+
+impl Epub {
+    pub fn new(title: String, css: String) -> Self {
+        Self {
+            title,
+            css,
+            chapters: Vec::new(),
+            images: Vec::new(),
+        }
+    }
+
+    /// Where the chapter with the given index is in the book, as linked from other chapters.
+    pub fn href(index: usize) -> String {
+        format!("ch{:03}.xhtml", index + 1)
+    }
+
+    /// Add the next chapter, with its body already rendered to HTML.
+    pub fn chapter(&mut self, title: &str, html: &str) {
+        let xhtml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n\
+            <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\">\n\
+            <head>\n<meta charset=\"utf-8\" />\n<title>{}</title>\n\
+            <link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\" />\n</head>\n\
+            <body>\n{}</body>\n</html>\n",
+            escape(title),
+            html
+        );
+        self.chapters.push((title.to_string(), xhtml));
+    }
+
+    /// Include the image at the path in the book, returning where it is as linked from chapters,
+    /// or nothing if the file isn't an image.
+    pub fn image(&mut self, path: &Path) -> Result<Option<String>> {
+        if let Some(image) = self.images.iter().find(|image| image.path == path) {
+            return Ok(Some(image.href.clone()));
+        }
+        let ext = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let media_type = match ext.as_str() {
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "svg" => "image/svg+xml",
+            "webp" => "image/webp",
+            _ => return Ok(None),
+        };
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let data = fs::read(path)
+            .with_context(|| format!("Unable to read image {}", path.to_string_lossy()))?;
+        let href = format!("images/{:03}.{}", self.images.len() + 1, ext);
+        self.images.push(Image {
+            path: path.to_path_buf(),
+            href: href.clone(),
+            media_type,
+            data,
+        });
+
+        Ok(Some(href))
+    }
+
+    /// Write out the book, which starts with its uncompressed mimetype as EPUB requires.
+    pub fn write<W: Write + Seek>(&self, writer: W) -> Result<()> {
+        let mut zip = ZipWriter::new(writer);
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        let deflated = SimpleFileOptions::default();
+
+        zip.start_file("mimetype", stored)?;
+        zip.write_all(b"application/epub+zip")?;
+        zip.start_file("META-INF/container.xml", deflated)?;
+        zip.write_all(CONTAINER.as_bytes())?;
+        zip.start_file("OEBPS/content.opf", deflated)?;
+        zip.write_all(self.package().as_bytes())?;
+        zip.start_file("OEBPS/nav.xhtml", deflated)?;
+        zip.write_all(self.nav().as_bytes())?;
+        zip.start_file("OEBPS/style.css", deflated)?;
+        zip.write_all(self.css.as_bytes())?;
+        for (i, (_, xhtml)) in self.chapters.iter().enumerate() {
+            zip.start_file(format!("OEBPS/{}", Self::href(i)), deflated)?;
+            zip.write_all(xhtml.as_bytes())?;
+        }
+        for image in &self.images {
+            zip.start_file(format!("OEBPS/{}", image.href), deflated)?;
+            zip.write_all(&image.data)?;
+        }
+        zip.finish()?;
+
+        Ok(())
+    }
+
+    /// The package document listing the book's metadata and files, and the order of its chapters.
+    fn package(&self) -> String {
+        // The identifier comes from the content, so exporting the same notes gives the same book
+        let mut content = self.title.clone();
+        for (_, xhtml) in &self.chapters {
+            content.push_str(xhtml);
+        }
+        let id = format!("{:x}", md5::compute(content));
+
+        let mut manifest = String::from(
+            "<item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n\
+            <item id=\"css\" href=\"style.css\" media-type=\"text/css\"/>\n",
+        );
+        let mut spine = String::new();
+        for i in 0..self.chapters.len() {
+            manifest.push_str(&format!(
+                "<item id=\"ch{}\" href=\"{}\" media-type=\"application/xhtml+xml\"/>\n",
+                i + 1,
+                Self::href(i)
+            ));
+            spine.push_str(&format!("<itemref idref=\"ch{}\"/>\n", i + 1));
+        }
+        for (i, image) in self.images.iter().enumerate() {
+            manifest.push_str(&format!(
+                "<item id=\"img{}\" href=\"{}\" media-type=\"{}\"/>\n",
+                i + 1,
+                image.href,
+                image.media_type
+            ));
+        }
+
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"id\">\n\
+            <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+            <dc:identifier id=\"id\">urn:jot:{}</dc:identifier>\n\
+            <dc:title>{}</dc:title>\n\
+            <dc:language>en</dc:language>\n\
+            <meta property=\"dcterms:modified\">{}</meta>\n\
+            </metadata>\n<manifest>\n{}</manifest>\n<spine>\n{}</spine>\n</package>\n",
+            id,
+            escape(&self.title),
+            Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+            manifest,
+            spine
+        )
+    }
+
+    /// The table of contents, with an entry for each chapter.
+    fn nav(&self) -> String {
+        let entries = self
+            .chapters
+            .iter()
+            .enumerate()
+            .map(|(i, (title, _))| {
+                format!(
+                    "<li><a href=\"{}\">{}</a></li>\n",
+                    Self::href(i),
+                    escape(title)
+                )
+            })
+            .collect::<String>();
+
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n\
+            <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\">\n\
+            <head>\n<meta charset=\"utf-8\" />\n<title>{}</title>\n</head>\n<body>\n\
+            <nav epub:type=\"toc\">\n<h1>{}</h1>\n<ol>\n{}</ol>\n</nav>\n</body>\n</html>\n",
+            escape(&self.title),
+            escape(&self.title),
+            entries
+        )
+    }
+}
@@ -0,0 +1,135 @@
+//! Converting notes to other formats, for `jot export`.
+//!
+//! The HTML in every format comes from the same renderer as `jot html`, which jot only uses to
+//! render. Plain text and the syntax tree in JSON come from parsing the Markdown with markdown-rs,
+//! as `mdast`, pinned to the version the renderer is built on and parsing with the same
+//! [`html::parse_options`].
+
+mod epub;
+
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use mdast::mdast::{ListItem, Node};
+use serde_json::json;
+
+use crate::{frontmatter, html, note};
+
+pub use epub::Epub;
+
+/// Parse a note into its Markdown syntax tree, with front matter as its own node.
+fn parse(md: &str) -> Result<Node> {
+    mdast::to_mdast(md, &html::parse_options())
+        .map_err(|err| anyhow!("Unable to parse Markdown: {}", err))
+}
+
+/// The note as plain text, with its front matter and the Markdown syntax stripped.
+pub fn text(md: &str) -> Result<String> {
+    let text = block(&parse(md)?).unwrap_or_default();
+
+    Ok(text.trim_end().to_string() + "\n")
+}
+
+/// The note as JSON, with its path relative to the root when it has one.
+pub fn json(md: &str, path: Option<&Path>, root: &Path) -> Result<serde_json::Value> {
+    let front_matter = frontmatter::parse(md)?;
+    let stem = path
+        .and_then(Path::file_stem)
+        .map(|stem| stem.to_string_lossy());
+
+    Ok(json!({
+        "path": path.map(|path| path.strip_prefix(root).unwrap_or(path).to_string_lossy()),
+        "id": stem,
+        "title": note::title(md, path),
+        "front_matter": front_matter,
+        "html": html::fragment(md)?,
+        "ast": parse(md)?,
+    }))
+}
+
+/// The text of a block, or nothing for blocks with no text such as front matter.
+fn block(node: &Node) -> Option<String> {
+    match node {
+        Node::Root(_) | Node::BlockQuote(_) | Node::FootnoteDefinition(_) => {
+            blocks(node.children()?, "\n\n")
+        }
+        Node::List(list) => {
+            let separator = if list.spread { "\n\n" } else { "\n" };
+            let mut number = list.start.unwrap_or(1);
+            let items = list.children.iter().map(|item| {
+                let mut marker = match list.ordered {
+                    true => format!("{}. ", number),
+                    false => "- ".to_string(),
+                };
+                number += 1;
+                let indent = " ".repeat(marker.len());
+                if let Node::ListItem(ListItem {
+                    checked: Some(checked),
+                    ..
+                }) = item
+                {
+                    marker.push_str(if *checked { "[x] " } else { "[ ] " });
+                }
+
+                // Line up the rest of the item, such as nested lists, after the bullet or number
+                let text = item
+                    .children()
+                    .and_then(|children| blocks(children, separator))
+                    .unwrap_or_default();
+                let lines = text.lines().enumerate().map(|(i, line)| match i {
+                    0 => format!("{}{}", marker, line),
+                    _ if line.is_empty() => String::new(),
+                    _ => format!("{}{}", indent, line),
+                });
+                lines.collect::<Vec<_>>().join("\n")
+            });
+            Some(items.collect::<Vec<_>>().join(separator))
+        }
+        Node::Table(table) => Some(
+            table
+                .children
+                .iter()
+                .map(|row| {
+                    row.children()
+                        .map(|cells| cells.iter().map(inline).collect::<Vec<_>>().join("\t"))
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        Node::Code(code) => Some(code.value.clone()),
+        Node::Math(math) => Some(math.value.clone()),
+        Node::Yaml(_)
+        | Node::Toml(_)
+        | Node::Definition(_)
+        | Node::ThematicBreak(_)
+        | Node::Html(_) => None,
+        node => Some(inline(node)),
+    }
+}
+
+/// The text of each block, separated by the separator.
+fn blocks(nodes: &[Node], separator: &str) -> Option<String> {
+    let text = nodes
+        .iter()
+        .filter_map(block)
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>();
+
+    (!text.is_empty()).then(|| text.join(separator))
+}
+
+/// The text inside a block, keeping the alt text of images and the line breaks.
+fn inline(node: &Node) -> String {
+    match node {
+        Node::Break(_) => "\n".to_string(),
+        Node::Image(image) => image.alt.clone(),
+        Node::ImageReference(image) => image.alt.clone(),
+        Node::FootnoteReference(footnote) => format!("[{}]", footnote.identifier),
+        Node::Html(_) => String::new(),
+        node => match node.children() {
+            Some(children) => children.iter().map(inline).collect(),
+            None => node.to_string(),
+        },
+    }
+}
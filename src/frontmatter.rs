@@ -0,0 +1,260 @@
+//! Helpers for reading the YAML front matter at the top of a note.
+
+use std::ops::Range;
+
+use anyhow::{bail, Context, Result};
+use serde_yaml::{Mapping, Value};
+
+/// Split a note into its raw YAML front matter, if it has any, and the remaining body.
+///
+/// Front matter must start on the first line with `---`, and ends with a line containing only `---`
+/// or `...`.
+pub(crate) fn split(md: &str) -> (Option<&str>, &str) {
+    match bounds(md) {
+        Some((yaml, end)) => (Some(&md[yaml]), &md[end..]),
+        None => (None, md),
+    }
+}
+
+/// The byte range of the YAML in the front matter, and where the body starts after the closing
+/// delimiter.
+fn bounds(md: &str) -> Option<(Range<usize>, usize)> {
+    let start = md.len()
+        - md.strip_prefix("---\n")
+            .or_else(|| md.strip_prefix("---\r\n"))?
+            .len();
+
+    let mut offset = start;
+    for line in md[start..].split_inclusive('\n') {
+        let trimmed = line.trim_end_matches(['\r', '\n']);
+        if trimmed == "---" || trimmed == "..." {
+            return Some((start..offset, offset + line.len()));
+        }
+        offset += line.len();
+    }
+
+    // No closing delimiter, so this was never front matter
+    None
+}
+
+/// Parse the front matter of a note into a mapping, which is empty if there is no front matter.
+pub(crate) fn parse(md: &str) -> Result<Mapping> {
+    match split(md).0 {
+        Some(yaml) if !yaml.trim().is_empty() => {
+            match serde_yaml::from_str::<Value>(yaml).context("Invalid front matter")? {
+                Value::Mapping(mapping) => Ok(mapping),
+                Value::Null => Ok(Mapping::new()),
+                _ => bail!("Invalid front matter, expected a mapping of keys to values"),
+            }
+        }
+        _ => Ok(Mapping::new()),
+    }
+}
+
+/// Read a front matter field that can be either a single string or a list of strings.
+pub(crate) fn strings(front_matter: &Mapping, key: &str) -> Vec<String> {
+    match front_matter.get(key) {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Sequence(seq)) => seq
+            .iter()
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Format a string as a YAML scalar, quoting and escaping it only where YAML requires.
+pub(crate) fn scalar(value: &str) -> Result<String> {
+    let yaml = serde_yaml::to_string(value).context("Unable to write front matter")?;
+    Ok(yaml.trim_end().to_string())
+}
+
+/// Format a value given on the command line as YAML. Numbers, booleans, null, and lists are kept as
+/// they are, and anything else is written as a string.
+pub(crate) fn value(input: &str) -> Result<String> {
+    match serde_yaml::from_str::<Value>(input) {
+        Ok(Value::Number(_) | Value::Bool(_) | Value::Null | Value::Sequence(_))
+            if !input.trim().is_empty() && !input.contains('\n') =>
+        {
+            Ok(input.trim().to_string())
+        }
+        _ => scalar(input),
+    }
+}
+
+/// Set a top level key in the front matter to the YAML value, replacing the existing value in
+/// place or adding the key at the end. Front matter is added to notes without any.
+///
+/// The rest of the note, including the formatting and comments of other keys, is left as it is.
+pub(crate) fn set(md: &str, key: &str, value: &str) -> String {
+    let newline = newline(md);
+    let line = format!("{}: {}{}", key, value, newline);
+    let Some((yaml, _)) = bounds(md) else {
+        let gap = if md.starts_with(['\n', '\r']) {
+            ""
+        } else {
+            newline
+        };
+        return format!("---{}{}---{}{}{}", newline, line, newline, gap, md);
+    };
+
+    match key_lines(&md[yaml.clone()], key) {
+        Some(lines) => splice(md, yaml.start + lines.start..yaml.start + lines.end, &line),
+        // The front matter is whole lines, so the key can go straight after it
+        None => splice(md, yaml.end..yaml.end, &line),
+    }
+}
+
+/// Remove a top level key and its value from the front matter, if it is there.
+pub(crate) fn unset(md: &str, key: &str) -> String {
+    match bounds(md).and_then(|(yaml, _)| Some((yaml.start, key_lines(&md[yaml], key)?))) {
+        Some((start, lines)) => splice(md, start + lines.start..start + lines.end, ""),
+        None => md.to_string(),
+    }
+}
+
+/// Rename a top level key in the front matter, keeping its value as it is. Errors if a key with
+/// the new name is already there.
+pub(crate) fn rename(md: &str, from: &str, to: &str) -> Result<String> {
+    let Some((yaml, _)) = bounds(md) else {
+        return Ok(md.to_string());
+    };
+    let Some(lines) = key_lines(&md[yaml.clone()], from) else {
+        return Ok(md.to_string());
+    };
+    if key_lines(&md[yaml.clone()], to).is_some() {
+        bail!("Unable to rename {} to {}, {} is already set", from, to, to);
+    }
+
+    // Swap only the key, which is up to the first colon on its line
+    let start = yaml.start + lines.start;
+    let colon = md[start..].find(':').map_or(start, |colon| start + colon);
+    Ok(splice(md, start..colon, to))
+}
+
+/// The byte range of the lines holding a top level key and its value, which continues over any
+/// indented lines and block list items after the key.
+fn key_lines(yaml: &str, key: &str) -> Option<Range<usize>> {
+    let mut found: Option<Range<usize>> = None;
+    let mut offset = 0;
+    for line in yaml.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        match &mut found {
+            None => {
+                let name = line.split_once(':').map(|(name, _)| name.trim_end());
+                let name = name.map(|name| name.trim_matches(['"', '\'']));
+                if !line.starts_with([' ', '\t', '#', '-']) && name == Some(key) {
+                    found = Some(start..offset);
+                }
+            }
+            // Blank lines only belong to the value if more of it follows them
+            Some(lines) => match line.trim().is_empty() {
+                true => continue,
+                false if line.starts_with([' ', '\t', '-']) => lines.end = offset,
+                false => break,
+            },
+        }
+    }
+
+    found
+}
+
+/// Replace a range of the text.
+fn splice(text: &str, range: Range<usize>, with: &str) -> String {
+    format!("{}{}{}", &text[..range.start], with, &text[range.end..])
+}
+
+/// The line ending the note uses.
+fn newline(md: &str) -> &'static str {
+    match md.contains("\r\n") {
+        true => "\r\n",
+        false => "\n",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str =
+        "---\ntitle: Plan\ntags:\n  - a\n\n  - b\nstatus: draft # for now\n---\n# Plan\n";
+
+    #[test]
+    fn splits_front_matter_from_the_body() {
+        assert_eq!(split("---\na: 1\n---\nBody\n"), (Some("a: 1\n"), "Body\n"));
+        assert_eq!(
+            split("---\r\na: 1\r\n...\r\nBody"),
+            (Some("a: 1\r\n"), "Body")
+        );
+        assert_eq!(split("---\n---\n"), (Some(""), ""));
+        assert_eq!(split("---\na: 1\n"), (None, "---\na: 1\n"));
+        assert_eq!(split("\n---\na: 1\n---\n"), (None, "\n---\na: 1\n---\n"));
+    }
+
+    #[test]
+    fn sets_keys_in_place() {
+        assert_eq!(
+            set(NOTE, "tags", "[c]"),
+            "---\ntitle: Plan\ntags: [c]\nstatus: draft # for now\n---\n# Plan\n"
+        );
+        assert_eq!(
+            set(NOTE, "rating", "4"),
+            "---\ntitle: Plan\ntags:\n  - a\n\n  - b\nstatus: draft # for now\nrating: 4\n---\n# Plan\n"
+        );
+        assert_eq!(
+            set("---\r\n\"title\": Old\r\n---\r\n", "title", "New"),
+            "---\r\ntitle: New\r\n---\r\n"
+        );
+    }
+
+    #[test]
+    fn adds_front_matter_to_notes_without_it() {
+        assert_eq!(set("# Plan\n", "a", "1"), "---\na: 1\n---\n\n# Plan\n");
+        assert_eq!(set("\n# Plan\n", "a", "1"), "---\na: 1\n---\n\n# Plan\n");
+    }
+
+    #[test]
+    fn unsets_keys_with_their_values() {
+        assert_eq!(
+            unset(NOTE, "tags"),
+            "---\ntitle: Plan\nstatus: draft # for now\n---\n# Plan\n"
+        );
+        assert_eq!(unset(NOTE, "missing"), NOTE);
+        assert_eq!(unset("# No front matter\n", "title"), "# No front matter\n");
+    }
+
+    #[test]
+    fn renames_keys() {
+        assert_eq!(
+            rename(NOTE, "status", "state").unwrap(),
+            NOTE.replace("status:", "state:")
+        );
+        assert_eq!(rename(NOTE, "missing", "other").unwrap(), NOTE);
+        assert_eq!(
+            rename(NOTE, "title", "tags").unwrap_err().to_string(),
+            "Unable to rename title to tags, tags is already set"
+        );
+    }
+
+    #[test]
+    fn ignores_nested_and_commented_keys() {
+        let md = "---\nauthor:\n  title: Nested\n# title: Comment\n---\n";
+        assert_eq!(unset(md, "title"), md);
+        assert_eq!(
+            set(md, "title", "Top"),
+            "---\nauthor:\n  title: Nested\n# title: Comment\ntitle: Top\n---\n"
+        );
+    }
+
+    #[test]
+    fn formats_command_line_values() {
+        assert_eq!(value("4").unwrap(), "4");
+        assert_eq!(value("true").unwrap(), "true");
+        assert_eq!(value("[a, b]").unwrap(), "[a, b]");
+        assert_eq!(value("plain text").unwrap(), "plain text");
+        assert_eq!(value("a: b").unwrap(), "'a: b'");
+        assert_eq!(value("").unwrap(), "''");
+    }
+}
@@ -0,0 +1,309 @@
+//! Committing and syncing the notes root with git.
+//!
+//! This drives the git command line rather than a library so that it behaves exactly as git does
+//! for the user, picking up their identity, credentials, hooks, and remotes. Git's own output is
+//! captured and only shown when something fails.
+
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{config::Config, frontmatter, note};
+
+/// The longest commit subject derived from a note.
+const MAX_SUBJECT: usize = 72;
+
+/// The git repository holding the notes root.
+pub struct Repo {
+    /// The notes root, which git commands are run from
+    dir: PathBuf,
+
+    /// The top level of the working tree
+    top: PathBuf,
+}
+
+/// What happened when syncing with the remote.
+pub struct Synced {
+    pub branch: String,
+    pub pulled: bool,
+    pub pushed: bool,
+}
+
+impl Repo {
+    /// Find the repository containing the notes root.
+    pub fn open(config: &Config) -> Result<Self> {
+        let dir = config.root().to_path_buf();
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&dir)
+            .args(["rev-parse", "--show-toplevel"])
+            .output()
+            .context("Unable to run git")?;
+        if !output.status.success() {
+            bail!(
+                "The notes root {} is not in a git repository",
+                dir.to_string_lossy()
+            );
+        }
+        let top = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+
+        Ok(Self { dir, top })
+    }
+
+    /// Stage and commit the given paths, or everything under the notes root if there are none,
+    /// returning false when there was nothing to commit.
+    pub fn commit(&self, paths: &[PathBuf], message: &str) -> Result<bool> {
+        let paths = self.pathspec(paths)?;
+        let with_paths = |args: &[&str]| -> Vec<OsString> {
+            args.iter()
+                .map(OsString::from)
+                .chain(paths.iter().map(|path| path.clone().into_os_string()))
+                .collect()
+        };
+
+        self.run(with_paths(&["add", "--all", "--"]))?;
+        let staged = self.git(with_paths(&["diff", "--cached", "--quiet", "--"]))?;
+        if staged.status.success() {
+            return Ok(false);
+        }
+
+        // Only commit these paths, leaving anything else the user has staged alone
+        self.run(with_paths(&[
+            "commit",
+            "--quiet",
+            "--message",
+            message,
+            "--",
+        ]))?;
+        Ok(true)
+    }
+
+    /// Changed and untracked notes under the notes root, as absolute paths.
+    pub fn changed(&self) -> Result<Vec<PathBuf>> {
+        let status = self.run([
+            "status",
+            "--porcelain",
+            "-z",
+            "--untracked-files=all",
+            "--",
+            ".",
+        ])?;
+
+        // Each entry is two status letters, a space, then the path relative to the top level. A
+        // rename is followed by an extra entry holding the original path, which is skipped
+        let mut changed = Vec::new();
+        let mut entries = status.split('\0').filter(|entry| !entry.is_empty());
+        while let Some(entry) = entries.next() {
+            if entry.starts_with('R') || entry.starts_with('C') {
+                entries.next();
+            }
+            if let Some(path) = entry.get(3..) {
+                changed.push(self.top.join(path));
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Pull with rebase from the remote then push to it, on the current branch.
+    ///
+    /// If the pull stops on conflicts, it is undone so that the working tree is left as it was,
+    /// and the conflicting files are reported in the error.
+    pub fn sync(&self, remote: &str, push: bool) -> Result<Synced> {
+        let branch = self
+            .run(["symbolic-ref", "--quiet", "--short", "HEAD"])
+            .context("Unable to sync without a branch checked out")?
+            .trim()
+            .to_string();
+        if self.rebasing()? {
+            bail!(
+                "A rebase is already in progress in {}, finish or abort it before syncing",
+                self.top.to_string_lossy()
+            );
+        }
+        self.run(["remote", "get-url", remote])
+            .with_context(|| format!("No git remote named '{}'", remote))?;
+
+        // Nothing to pull until the branch has been pushed for the first time
+        let heads = self.git(["ls-remote", "--exit-code", "--heads", remote, &branch])?;
+        let pulled = match heads.status.code() {
+            Some(0) => {
+                self.pull(remote, &branch)?;
+                true
+            }
+            Some(2) => false,
+            _ => bail!(
+                "Unable to reach remote '{}': {}",
+                remote,
+                String::from_utf8_lossy(&heads.stderr).trim()
+            ),
+        };
+
+        if push {
+            self.run(["push", "--quiet", remote, &branch])?;
+        }
+
+        Ok(Synced {
+            branch,
+            pulled,
+            pushed: push,
+        })
+    }
+
+    fn pull(&self, remote: &str, branch: &str) -> Result<()> {
+        let output = self.git(["pull", "--quiet", "--rebase", "--autostash", remote, branch])?;
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let conflicts = self.run(["diff", "--name-only", "--diff-filter=U"])?;
+        if self.rebasing()? {
+            self.run(["rebase", "--abort"])
+                .context("Unable to undo the stopped pull, finish or abort the rebase by hand")?;
+        }
+        if conflicts.trim().is_empty() {
+            bail!(
+                "Unable to pull from {}: {}",
+                remote,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        let conflicts = conflicts
+            .lines()
+            .map(|path| format!("  {}", self.top.join(path).to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        bail!(
+            "Changes from {remote}/{branch} conflict with local changes in:\n{conflicts}\n\
+             The pull was undone and local commits were kept. Merge by hand with \
+             `git pull --rebase {remote} {branch}` in the notes root."
+        );
+    }
+
+    /// Whether a rebase has stopped part way through.
+    fn rebasing(&self) -> Result<bool> {
+        ["rebase-merge", "rebase-apply"]
+            .iter()
+            .try_fold(false, |found, name| {
+                let path = self.run(["rev-parse", "--git-path", name])?;
+                Ok(found || self.dir.join(path.trim()).exists())
+            })
+    }
+
+    /// The paths as a pathspec, defaulting to the whole notes root.
+    ///
+    /// Git runs from the notes root, so relative paths are first made absolute. They are also
+    /// canonicalized where they exist so that git can match them against the working tree when
+    /// they are reached through a symlink.
+    fn pathspec(&self, paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        if paths.is_empty() {
+            return Ok(vec![PathBuf::from(".")]);
+        }
+
+        let cwd = env::current_dir()?;
+        Ok(paths
+            .iter()
+            .map(|path| path.canonicalize().unwrap_or_else(|_| cwd.join(path)))
+            .collect())
+    }
+
+    /// Run git in the notes root, returning its output whether or not it succeeded.
+    fn git<I, S>(&self, args: I) -> Result<Output>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        Command::new("git")
+            .arg("-C")
+            .arg(&self.dir)
+            .args(args)
+            .output()
+            .context("Unable to run git")
+    }
+
+    /// Run git in the notes root, returning its stdout, or its stderr as an error if it failed.
+    fn run<I, S>(&self, args: I) -> Result<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let args = args
+            .into_iter()
+            .map(|arg| arg.as_ref().to_os_string())
+            .collect::<Vec<_>>();
+        let output = self.git(&args)?;
+        if !output.status.success() {
+            bail!(
+                "git {} failed: {}",
+                args.first()
+                    .map(|arg| arg.to_string_lossy())
+                    .unwrap_or_default(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Commit the note if automatic commits are on, syncing afterwards if that is on too.
+pub fn auto_commit(config: &Config, note: &Path) -> Result<()> {
+    if !config.git.auto_commit {
+        return Ok(());
+    }
+
+    let repo = Repo::open(config)?;
+    let notes = [note.to_path_buf()];
+    repo.commit(&notes, &message(&notes)?)
+        .with_context(|| format!("Unable to commit {}", note.to_string_lossy()))?;
+    if config.git.auto_sync {
+        repo.sync(&config.git.remote, true)?;
+    }
+
+    Ok(())
+}
+
+/// A commit message for the given notes, from the note's title or first line when there is only
+/// one.
+pub fn message(notes: &[PathBuf]) -> Result<String> {
+    match notes {
+        [note] => subject(note),
+        _ => Ok(format!("Update {} notes", notes.len())),
+    }
+}
+
+/// The note's title, otherwise its first line of text, or its filename if it is empty or was
+/// removed.
+fn subject(note: &Path) -> Result<String> {
+    let name = note
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let md = match note.is_file() {
+        true => fs::read_to_string(note)?,
+        false => return Ok(format!("Remove {}", name)),
+    };
+
+    let title = note::declared_title(&md)
+        .or_else(|| {
+            frontmatter::split(&md)
+                .1
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(str::to_string)
+        })
+        .unwrap_or(name);
+
+    Ok(match title.char_indices().nth(MAX_SUBJECT) {
+        Some((end, _)) => format!("{}...", title[..end].trim_end()),
+        None => title,
+    })
+}
@@ -0,0 +1,190 @@
+//! Running the user's hook commands around creating, editing, and rendering notes.
+//!
+//! Each hook is a shell command from the `[hooks]` config section. The note path is passed as `$1`
+//! and any output path as `$2`, with the same paths, the notes root, and the note's front matter
+//! also available as environment variables. Hook output is sent to stderr so that it never mixes
+//! with anything jot writes to stdout, such as the path of a new note or rendered HTML.
+
+use std::{
+    fs, io,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::{bail, Context, Result};
+use serde_yaml::Value;
+
+use crate::{config::Config, frontmatter};
+
+/// The points at which a hook can run.
+#[derive(Debug, Clone, Copy)]
+pub enum Hook {
+    PostCreate,
+    PostEdit,
+    PreRender,
+    PostRender,
+}
+
+impl Hook {
+    /// The hook's name as used in the config.
+    fn name(self) -> &'static str {
+        match self {
+            Hook::PostCreate => "post_create",
+            Hook::PostEdit => "post_edit",
+            Hook::PreRender => "pre_render",
+            Hook::PostRender => "post_render",
+        }
+    }
+
+    /// The configured command for the hook, if any. An empty command disables the hook, so a
+    /// notebook or local config can switch off a hook set globally.
+    fn command(self, config: &Config) -> Option<&str> {
+        let command = match self {
+            Hook::PostCreate => &config.hooks.post_create,
+            Hook::PostEdit => &config.hooks.post_edit,
+            Hook::PreRender => &config.hooks.pre_render,
+            Hook::PostRender => &config.hooks.post_render,
+        };
+        command
+            .as_deref()
+            .filter(|command| !command.trim().is_empty())
+    }
+}
+
+/// Run the hook if one is configured, for the note with the given markdown content.
+///
+/// A failing hook is reported on stderr, and only returns an error when hooks are configured as
+/// fatal.
+pub fn run(
+    config: &Config,
+    hook: Hook,
+    note: Option<&Path>,
+    output: Option<&Path>,
+    md: &str,
+) -> Result<()> {
+    let Some(command) = hook.command(config) else {
+        return Ok(());
+    };
+
+    let result = execute(config, hook, command, note, output, md);
+    match (result, config.hooks.fatal) {
+        (Ok(()), _) => Ok(()),
+        (Err(err), true) => Err(err),
+        (Err(err), false) => {
+            eprintln!("Warning: {:#}", err);
+            Ok(())
+        }
+    }
+}
+
+/// Run the pre-render hook for a note about to be rendered, returning its markdown as the hook left
+/// it so that any changes the hook makes to the note file are rendered. Markdown from stdin is
+/// returned unchanged.
+pub fn pre_render(
+    config: &Config,
+    note: Option<&Path>,
+    output: Option<&Path>,
+    md: String,
+) -> Result<String> {
+    run(config, Hook::PreRender, note, output, &md)?;
+
+    match note {
+        Some(note) if Hook::PreRender.command(config).is_some() => fs::read_to_string(note)
+            .with_context(|| format!("Unable to read note {}", note.to_string_lossy())),
+        _ => Ok(md),
+    }
+}
+
+fn execute(
+    config: &Config,
+    hook: Hook,
+    command: &str,
+    note: Option<&Path>,
+    output: Option<&Path>,
+    md: &str,
+) -> Result<()> {
+    let note = note.map(Path::to_string_lossy).unwrap_or_default();
+    let output = output.map(Path::to_string_lossy).unwrap_or_default();
+
+    let mut cmd = shell(command, hook, &note, &output);
+    cmd.env("JOT_HOOK", hook.name())
+        .env("JOT_NOTE", note.as_ref())
+        .env("JOT_OUTPUT", output.as_ref())
+        .env("JOT_ROOT", config.root())
+        .env(
+            "JOT_NOTEBOOK",
+            config.notebook.as_deref().unwrap_or_default(),
+        )
+        .stdin(Stdio::null())
+        .stdout(io::stderr());
+
+    // Front matter that can't be parsed shouldn't stop the hook, it just gets no metadata
+    for (key, value) in frontmatter::parse(md).unwrap_or_default() {
+        if let (Some(key), Some(value)) = (key.as_str(), env_value(&value)) {
+            cmd.env(format!("JOT_META_{}", env_key(key)), value);
+        }
+    }
+
+    let status = cmd
+        .status()
+        .with_context(|| format!("Unable to run {} hook '{}'", hook.name(), command))?;
+    if !status.success() {
+        match status.code() {
+            Some(code) => bail!("The {} hook exited with status {}", hook.name(), code),
+            None => bail!("The {} hook was terminated by a signal", hook.name()),
+        }
+    }
+
+    Ok(())
+}
+
+/// Build a command running the hook through the platform's shell.
+#[cfg(not(windows))]
+fn shell(command: &str, hook: Hook, note: &str, output: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    // The first argument after the command becomes $0, the rest the positional arguments
+    cmd.arg("-c")
+        .arg(command)
+        .arg(format!("jot-{}", hook.name()))
+        .arg(note)
+        .arg(output);
+    cmd
+}
+
+/// Build a command running the hook through the platform's shell.
+///
+/// There are no positional arguments with `cmd`, so hooks use the environment variables instead.
+#[cfg(windows)]
+fn shell(command: &str, _hook: Hook, _note: &str, _output: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+/// Turn a front matter key into an environment variable suffix, e.g. `due-date` to `DUE_DATE`.
+fn env_key(key: &str) -> String {
+    key.chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c.to_ascii_uppercase(),
+            false => '_',
+        })
+        .collect()
+}
+
+/// Format a front matter value for the environment, with lists separated by commas. Nested
+/// mappings have no sensible flat form and are skipped.
+fn env_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Sequence(seq) => Some(
+            seq.iter()
+                .filter_map(env_value)
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        Value::Tagged(tagged) => env_value(&tagged.value),
+        Value::Null | Value::Mapping(_) => None,
+    }
+}
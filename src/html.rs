@@ -4,23 +4,38 @@
 use std::{
     fs::File,
     io::{self, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use markdown::Markdown;
+use mdast::{Constructs, ParseOptions};
+use resolve_path::PathResolveExt;
+
+use crate::{args::StyleArgs, config::Config, frontmatter, theme::Theme};
+
+/// The document title when the note has none.
+const DEFAULT_TITLE: &str = "Jot Note";
 
 pub struct HtmlWriter<W> {
     writer: W,
-    css: Option<PathBuf>,
+    title: String,
+    theme: Theme,
+    css: Vec<PathBuf>,
 }
 
 impl<W> HtmlWriter<W>
 where
     W: Write,
 {
-    pub fn new(writer: W, css: Option<PathBuf>) -> Self {
-        Self { writer, css }
+    /// Create a writer for a document with the given title, falling back to a generic title.
+    pub fn new(writer: W, title: Option<String>, theme: Theme, css: Vec<PathBuf>) -> Self {
+        Self {
+            writer,
+            title: title.unwrap_or(DEFAULT_TITLE.to_string()),
+            theme,
+            css,
+        }
     }
 
     /// Write out the entire HTML document with article content given by the Markdown content in
@@ -37,13 +52,23 @@ where
 
     /// Write only the head component of the HTML document.
     ///
-    /// Includes styles if they exist, skipping them silently if they don't.
+    /// Inlines the theme, then each stylesheet in order, erroring if any of them can't be read.
     pub fn write_head(&mut self) -> Result<()> {
         writeln!(
             self.writer,
-            "<head>\n<meta charset=\"utf-8\" />\n<title>Jot Note</title>"
+            "<head>\n<meta charset=\"utf-8\" />\n<title>{}</title>",
+            escape(&self.title)
         )?;
-        if let Some(mut file) = self.css.as_ref().and_then(|p| File::open(p).ok()) {
+        if let Some(theme) = self.theme.css() {
+            writeln!(self.writer, "<style>")?;
+            for css in theme {
+                self.writer.write_all(css.as_bytes())?;
+            }
+            writeln!(self.writer, "</style>")?;
+        }
+        for path in &self.css {
+            let mut file = File::open(path)
+                .with_context(|| format!("Unable to read stylesheet {}", path.to_string_lossy()))?;
             writeln!(self.writer, "<style>")?;
             io::copy(&mut file, &mut self.writer)?;
             writeln!(self.writer, "</style>")?;
@@ -60,3 +85,83 @@ where
         md.write_html(&mut self.writer)
     }
 }
+
+/// The options the renderer parses Markdown with, GFM along with YAML front matter, for anything
+/// else in jot that parses notes, so that it sees the same document that is rendered.
+pub fn parse_options() -> ParseOptions {
+    ParseOptions {
+        constructs: Constructs {
+            frontmatter: true,
+            ..Constructs::gfm()
+        },
+        ..ParseOptions::gfm()
+    }
+}
+
+/// Render the body of a note as an HTML fragment, leaving out its front matter.
+pub fn fragment(md: &str) -> Result<String> {
+    let mut html = Vec::new();
+    Markdown::new(frontmatter::split(md).1).write_html(&mut html)?;
+
+    Ok(String::from_utf8(html)?)
+}
+
+/// Escape text for use in HTML.
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Choose the theme to render with, with an explicit theme argument taking priority.
+pub fn theme(config: &Config, style: &StyleArgs) -> Theme {
+    match (style.theme, style.no_css) {
+        (Some(theme), _) => theme,
+        (None, true) => Theme::None,
+        (None, false) => config.theme,
+    }
+}
+
+/// Gather the stylesheets to render a note with, in the order they are injected.
+///
+/// Stylesheets come from the config (or a discovered jot.css), then print stylesheets when
+/// rendering for print, then the note's `css` front matter, then the css arguments. Front matter
+/// paths are relative to the note, or the current directory when reading from stdin. Every
+/// missing stylesheet is reported rather than being silently skipped.
+pub fn stylesheets(
+    config: &Config,
+    style: &StyleArgs,
+    md: &str,
+    input: Option<&Path>,
+    print: bool,
+) -> Result<Vec<PathBuf>> {
+    let mut css = Vec::new();
+
+    if !style.no_css {
+        css.extend(config.css.iter().cloned());
+        if print {
+            css.extend(config.pdf_css.iter().cloned());
+        }
+
+        let base = match input.and_then(Path::parent) {
+            Some(dir) => dir.to_path_buf(),
+            None => std::env::current_dir()?,
+        };
+        for path in frontmatter::strings(&frontmatter::parse(md)?, "css") {
+            css.push(Path::new(&path).try_resolve_in(&base)?.to_path_buf());
+        }
+    }
+    css.extend(style.css.iter().cloned());
+
+    let missing = css
+        .iter()
+        .filter(|path| !path.is_file())
+        .map(|path| path.to_string_lossy())
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        bail!("Stylesheets not found: {}", missing.join(", "));
+    }
+
+    Ok(css)
+}
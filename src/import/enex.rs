@@ -0,0 +1,543 @@
+//! Reading Evernote ENEX exports, converting each note's ENML content to Markdown.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use roxmltree::{Document, Node, ParsingOptions};
+
+use crate::{config::Timezone, frontmatter};
+
+use super::{Import, Item};
+
+/// How Evernote writes dates, always in UTC.
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Read the notes in an ENEX file. Resources become attachments in a folder named after the file,
+/// linked from where the note shows them.
+pub fn enex(file: &Path, timezone: Timezone) -> Result<Import> {
+    let xml = fs::read_to_string(file)
+        .with_context(|| format!("Unable to read {}", file.to_string_lossy()))?;
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let doc = Document::parse_with_options(&xml, options)
+        .with_context(|| format!("Invalid ENEX file {}", file.to_string_lossy()))?;
+    let dir = file.with_extension("");
+
+    let mut import = Import::default();
+    let notes = doc
+        .root_element()
+        .children()
+        .filter(|node| node.has_tag_name("note"));
+    for (i, note) in notes.enumerate() {
+        let title = text(note, "title").filter(|title| !title.trim().is_empty());
+        let name = title.clone().unwrap_or_else(|| format!("Note {}", i + 1));
+        let path = dir.join(i.to_string()).join("note.md");
+
+        // Resources are shown in the content by the MD5 hash of their data
+        let mut names = HashMap::new();
+        for (j, resource) in note
+            .children()
+            .filter(|node| node.has_tag_name("resource"))
+            .enumerate()
+        {
+            let data = text(resource, "data")
+                .map(|data| data.split_whitespace().collect::<String>())
+                .and_then(|data| STANDARD.decode(data).ok());
+            let Some(data) = data else {
+                import.problem(&name, format!("Unable to read attachment {}", j + 1));
+                continue;
+            };
+            let mime = text(resource, "mime").unwrap_or_default();
+            let filename = resource
+                .children()
+                .find(|node| node.has_tag_name("resource-attributes"))
+                .and_then(|attributes| text(attributes, "file-name"))
+                .filter(|filename| !filename.contains(['/', '\\']) && !filename.is_empty())
+                .unwrap_or_else(|| format!("attachment-{}.{}", j + 1, extension(&mime)));
+
+            // Keep the names unique within the note, as they all go in one folder
+            let filename = match names.values().any(|(taken, _)| *taken == filename) {
+                true => format!("{}-{}", j + 1, filename),
+                false => filename,
+            };
+            let hash = format!("{:x}", md5::compute(&data));
+            import
+                .attachments
+                .insert(path.with_file_name(&filename), data);
+            names.insert(hash, (filename, mime.starts_with("image/")));
+        }
+
+        let content = text(note, "content").unwrap_or_default();
+        let mut converter = Converter {
+            out: String::new(),
+            names: &names,
+            lists: Vec::new(),
+            missing: 0,
+        };
+        let body = match converter.convert(&content) {
+            Ok(body) => body,
+            Err(err) => {
+                import.problem(&name, format!("{:#}", err));
+                continue;
+            }
+        };
+        if converter.missing > 0 {
+            import.problem(
+                &name,
+                format!("{} attachments not found in the export", converter.missing),
+            );
+        }
+
+        let mut front_matter = String::new();
+        let tags = note
+            .children()
+            .filter(|node| node.has_tag_name("tag"))
+            .filter_map(|tag| tag.text())
+            .map(|tag| serde_json::to_string(tag.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !tags.is_empty() {
+            front_matter.push_str(&format!("tags: [{}]\n", tags.join(", ")));
+        }
+        let url = note
+            .children()
+            .find(|node| node.has_tag_name("note-attributes"))
+            .and_then(|attributes| text(attributes, "source-url"));
+        if let Some(url) = url {
+            front_matter.push_str(&format!("source: {}\n", frontmatter::scalar(&url)?));
+        }
+        let md = match front_matter.is_empty() {
+            true => body,
+            false => format!("---\n{}---\n\n{}", front_matter, body),
+        };
+
+        let created = text(note, "created")
+            .and_then(|date| NaiveDateTime::parse_from_str(&date, DATE_FORMAT).ok())
+            .map(|date| timezone.convert(Utc.from_utc_datetime(&date)));
+        import.items.push(Item {
+            name,
+            path,
+            md,
+            title,
+            created,
+        });
+    }
+
+    Ok(import)
+}
+
+/// The text of the named child element.
+fn text(node: Node, name: &str) -> Option<String> {
+    node.children()
+        .find(|child| child.has_tag_name(name))
+        .map(|child| child.text().unwrap_or_default().to_string())
+}
+
+/// A file extension for attachments without a filename.
+fn extension(mime: &str) -> &str {
+    match mime {
+        "image/jpeg" => "jpg",
+        "image/svg+xml" => "svg",
+        "application/pdf" => "pdf",
+        "text/plain" => "txt",
+        mime => mime
+            .split_once('/')
+            .map(|(_, subtype)| subtype)
+            .filter(|subtype| subtype.chars().all(|c| c.is_ascii_alphanumeric()))
+            .unwrap_or("bin"),
+    }
+}
+
+/// Converts ENML, Evernote's XHTML, to Markdown.
+struct Converter<'a> {
+    out: String,
+
+    /// The filenames of the note's attachments by hash, and whether each is an image
+    names: &'a HashMap<String, (String, bool)>,
+
+    /// The lists being written, with the next number for ordered lists
+    lists: Vec<Option<usize>>,
+
+    /// The number of attachments shown in the note that aren't in the export
+    missing: usize,
+}
+
+impl Converter<'_> {
+    fn convert(&mut self, enml: &str) -> Result<String> {
+        // ENML comes with a DTD defining HTML entities, but roxmltree won't fetch it
+        let enml = enml
+            .replace("&nbsp;", "&#160;")
+            .replace("&mdash;", "&#8212;")
+            .replace("&ndash;", "&#8211;")
+            .replace("&hellip;", "&#8230;");
+        let enml = match (enml.find("<!DOCTYPE"), enml.find("<en-note")) {
+            (Some(start), Some(note)) if start < note => {
+                format!("{}{}", &enml[..start], &enml[note..])
+            }
+            _ => enml,
+        };
+        let doc = Document::parse(&enml).context("Invalid note content")?;
+        self.children(doc.root_element());
+
+        // Tidy the whitespace left between blocks
+        let mut md = String::new();
+        let mut blank = 0;
+        for line in self.out.lines().map(str::trim_end) {
+            blank = if line.trim().is_empty() { blank + 1 } else { 0 };
+            if blank < 2 {
+                md.push_str(line);
+                md.push('\n');
+            }
+        }
+
+        Ok(md.trim().to_string() + "\n")
+    }
+
+    fn children(&mut self, node: Node) {
+        for child in node.children() {
+            self.node(child);
+        }
+    }
+
+    fn node(&mut self, node: Node) {
+        if node.is_text() {
+            let text = node.text().unwrap_or_default();
+            let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if text.starts_with(char::is_whitespace) && !self.at_line_start() {
+                self.out.push(' ');
+            }
+            self.out.push_str(&collapsed);
+            if text.ends_with(char::is_whitespace) && !collapsed.is_empty() {
+                self.out.push(' ');
+            }
+            return;
+        }
+        if !node.is_element() {
+            return;
+        }
+
+        let tag = node.tag_name().name();
+        match tag {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.block();
+                let level = tag[1..].parse().unwrap_or(1);
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+                self.children(node);
+                self.block();
+            }
+            "p" | "blockquote" | "table" => {
+                self.block();
+                match tag {
+                    "blockquote" => self.prefixed(node, "> "),
+                    "table" => self.table(node),
+                    _ => self.children(node),
+                }
+                self.block();
+            }
+            "div" => {
+                self.line();
+                self.children(node);
+                self.line();
+            }
+            "br" => self.out.push('\n'),
+            "hr" => {
+                self.block();
+                self.out.push_str("---");
+                self.block();
+            }
+            "b" | "strong" => self.wrap(node, "**"),
+            "i" | "em" => self.wrap(node, "*"),
+            "s" | "strike" | "del" => self.wrap(node, "~~"),
+            "code" => self.wrap(node, "`"),
+            "pre" => {
+                self.block();
+                self.out.push_str("```\n");
+                self.out.push_str(
+                    node.descendants()
+                        .filter_map(|n| n.text())
+                        .collect::<String>()
+                        .trim_end(),
+                );
+                self.out.push_str("\n```");
+                self.block();
+            }
+            "a" => {
+                self.out.push('[');
+                self.children(node);
+                self.out.push_str(&format!(
+                    "]({})",
+                    node.attribute("href").unwrap_or_default()
+                ));
+            }
+            "img" => {
+                let src = node.attribute("src").unwrap_or_default();
+                self.out.push_str(&format!(
+                    "![{}]({})",
+                    node.attribute("alt").unwrap_or_default(),
+                    src
+                ));
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.block();
+                }
+                self.lists.push((tag == "ol").then_some(1));
+                self.children(node);
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.block();
+                }
+            }
+            "li" => {
+                self.line();
+                let depth = self.lists.len().saturating_sub(1);
+                let marker = match self.lists.last_mut() {
+                    Some(Some(next)) => {
+                        *next += 1;
+                        format!("{}. ", *next - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.out.push_str(&"  ".repeat(depth));
+                self.out.push_str(&marker);
+                self.children(node);
+                self.line();
+            }
+            "en-todo" => {
+                let checked = node.attribute("checked") == Some("true");
+                if self.at_line_start() {
+                    self.out.push_str("- ");
+                }
+                self.out.push_str(if checked { "[x] " } else { "[ ] " });
+            }
+            "en-media" => {
+                let hash = node.attribute("hash").unwrap_or_default();
+                match self.names.get(hash) {
+                    Some((name, image)) => {
+                        let bang = if *image { "!" } else { "" };
+                        self.out
+                            .push_str(&format!("{}[{}](<{}>)", bang, name, name));
+                    }
+                    None => self.missing += 1,
+                }
+            }
+            _ => self.children(node),
+        }
+    }
+
+    /// Wrap the node's content in Markdown emphasis or code markers.
+    fn wrap(&mut self, node: Node, marker: &str) {
+        self.out.push_str(marker);
+        self.children(node);
+        self.out.push_str(marker);
+    }
+
+    /// Write the node's content with each line prefixed, for block quotes.
+    fn prefixed(&mut self, node: Node, prefix: &str) {
+        let before = std::mem::take(&mut self.out);
+        self.children(node);
+        let quoted = self
+            .out
+            .trim()
+            .lines()
+            .map(|line| format!("{}{}", prefix, line).trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.out = before + &quoted;
+    }
+
+    /// Write a table with the first row as the header.
+    fn table(&mut self, node: Node) {
+        let rows = node
+            .descendants()
+            .filter(|row| row.has_tag_name("tr"))
+            .collect::<Vec<_>>();
+        for (i, row) in rows.iter().enumerate() {
+            let cells = row
+                .children()
+                .filter(|cell| cell.has_tag_name("td") || cell.has_tag_name("th"))
+                .map(|cell| {
+                    let before = std::mem::take(&mut self.out);
+                    self.children(cell);
+                    let text = std::mem::replace(&mut self.out, before);
+                    text.split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                        .replace('|', "\\|")
+                })
+                .collect::<Vec<_>>();
+            self.out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            if i == 0 {
+                self.out
+                    .push_str(&format!("|{}\n", " --- |".repeat(cells.len().max(1))));
+            }
+        }
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.out.is_empty() || self.out.ends_with('\n')
+    }
+
+    /// Start a new line unless already at the start of one.
+    fn line(&mut self) {
+        if !self.at_line_start() {
+            self.out.push('\n');
+        }
+    }
+
+    /// Leave a blank line before what comes next.
+    fn block(&mut self) {
+        self.line();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The Markdown for ENML content, with the attachments given by hash.
+    fn convert(enml: &str, names: &[(&str, &str, bool)]) -> (String, usize) {
+        let names = names
+            .iter()
+            .map(|(hash, name, image)| (hash.to_string(), (name.to_string(), *image)))
+            .collect();
+        let mut converter = Converter {
+            out: String::new(),
+            names: &names,
+            lists: Vec::new(),
+            missing: 0,
+        };
+        let md = converter.convert(enml).unwrap();
+        (md, converter.missing)
+    }
+
+    #[test]
+    fn converts_text_formatting() {
+        let enml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE en-note SYSTEM "http://xml.evernote.com/pub/enml2.dtd">
+<en-note><h2>Plan</h2><div>Some <b>bold</b>, <i>italic</i>&nbsp;and <code>code</code></div>
+<div><a href="https://example.com">a link</a><br/>next&mdash;line</div><hr/>
+<blockquote><p>quoted</p><p>twice</p></blockquote></en-note>"#;
+        assert_eq!(
+            convert(enml, &[]).0,
+            "## Plan\n\nSome **bold**, *italic* and `code`\n[a link](https://example.com)\nnext\u{2014}line\n\n---\n\n> quoted\n>\n> twice\n"
+        );
+    }
+
+    #[test]
+    fn converts_lists_and_todos() {
+        let enml =
+            "<en-note><ul><li>one<ol><li>first</li><li>second</li></ol></li><li>two</li></ul>\
+            <div><en-todo checked=\"true\"/>done</div><div><en-todo/>to do</div></en-note>";
+        assert_eq!(
+            convert(enml, &[]).0,
+            "- one\n  1. first\n  2. second\n- two\n\n- [x] done\n- [ ] to do\n"
+        );
+    }
+
+    #[test]
+    fn converts_tables() {
+        let enml = "<en-note><table><tr><th>Name</th><th>Note</th></tr>\
+            <tr><td>a | b</td><td><b>c</b></td></tr></table></en-note>";
+        assert_eq!(
+            convert(enml, &[]).0,
+            "| Name | Note |\n| --- | --- |\n| a \\| b | **c** |\n"
+        );
+    }
+
+    #[test]
+    fn links_media_by_hash() {
+        let enml = "<en-note><en-media hash=\"aa\" type=\"image/png\"/> \
+            <en-media hash=\"bb\" type=\"application/pdf\"/><en-media hash=\"cc\"/></en-note>";
+        let names = [("aa", "my chart.png", true), ("bb", "doc.pdf", false)];
+        assert_eq!(
+            convert(enml, &names),
+            (
+                "![my chart.png](<my chart.png>) [doc.pdf](<doc.pdf>)\n".to_string(),
+                1
+            )
+        );
+    }
+
+    #[test]
+    fn names_attachments_without_a_filename_by_type() {
+        assert_eq!(extension("image/jpeg"), "jpg");
+        assert_eq!(extension("image/png"), "png");
+        assert_eq!(extension("application/vnd.ms-excel"), "bin");
+        assert_eq!(extension(""), "bin");
+    }
+
+    #[test]
+    fn reads_notes_from_an_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("export.enex");
+        let data = STANDARD.encode("hello");
+        let hash = format!("{:x}", md5::compute("hello"));
+        fs::write(
+            &file,
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE en-export SYSTEM "http://xml.evernote.com/pub/evernote-export4.dtd">
+<en-export>
+<note><title>Trip</title><created>20230405T120000Z</created><tag>travel</tag><tag>2023</tag>
+<note-attributes><source-url>https://example.com/a: b</source-url></note-attributes>
+<content><![CDATA[<en-note><en-media hash="{hash}"/><en-media hash="{hash}"/></en-note>]]></content>
+<resource><data>{data}</data><mime>text/plain</mime>
+<resource-attributes><file-name>a.txt</file-name></resource-attributes></resource>
+<resource><data>{data}</data><mime>text/plain</mime>
+<resource-attributes><file-name>a.txt</file-name></resource-attributes></resource>
+<resource><data>!!</data></resource>
+</note>
+<note><title> </title><content><![CDATA[<en-note><p>unclosed</en-note>]]></content></note>
+<note><content><![CDATA[<en-note>Untitled</en-note>]]></content></note>
+</en-export>"#
+            ),
+        )
+        .unwrap();
+
+        let tokyo = Timezone::Named("Asia/Tokyo".parse().unwrap());
+        let import = enex(&file, tokyo).unwrap();
+        let notes = dir.path().join("export");
+        let [trip, untitled] = &import.items[..] else {
+            panic!("Expected 2 notes, read {}", import.items.len());
+        };
+        assert_eq!(trip.name, "Trip");
+        assert_eq!(trip.title.as_deref(), Some("Trip"));
+        assert_eq!(trip.path, notes.join("0/note.md"));
+        assert_eq!(
+            trip.created.unwrap().to_rfc3339(),
+            "2023-04-05T21:00:00+09:00"
+        );
+        assert_eq!(
+            trip.md,
+            "---\ntags: [\"travel\", \"2023\"]\nsource: 'https://example.com/a: b'\n---\n\n[2-a.txt](<2-a.txt>)[2-a.txt](<2-a.txt>)\n"
+        );
+        assert_eq!(untitled.name, "Note 3");
+        assert_eq!(untitled.title, None);
+        assert_eq!(untitled.created, None);
+        assert_eq!(untitled.md, "Untitled\n");
+
+        let mut attachments = import.attachments.keys().cloned().collect::<Vec<_>>();
+        attachments.sort();
+        assert_eq!(
+            attachments,
+            [notes.join("0/2-a.txt"), notes.join("0/a.txt")]
+        );
+        let problems = import
+            .problems
+            .iter()
+            .map(|problem| (problem.name.as_str(), problem.reason.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(problems[0], ("Trip", "Unable to read attachment 3"));
+        assert_eq!(problems[1].0, "Note 2");
+        assert!(problems[1].1.starts_with("Invalid note content"));
+        assert_eq!(problems.len(), 2);
+    }
+}
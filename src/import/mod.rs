@@ -0,0 +1,280 @@
+//! Reading notes exported from other note apps, for `jot import`.
+//!
+//! Each format is read into [`Item`]s holding Markdown, which may still have the source's own
+//! front matter, along with the path links in it are relative to. Files from a directory keep
+//! their own path, while notes from a single export file are given a path in a folder named after
+//! the file, with their attachments alongside. Anything that can't be read is reported as a
+//! [`Problem`] rather than stopping the import.
+
+mod enex;
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+
+use crate::{
+    config::Timezone,
+    frontmatter,
+    links::{self, Kind},
+    note,
+};
+
+pub use enex::enex;
+
+/// The front matter keys holding the date a note was created in other apps, in order of
+/// preference.
+const CREATED_KEYS: [&str; 4] = ["created", "date", "timestamp", "created_at"];
+
+/// A note read from the source.
+pub struct Item {
+    /// What the note is called in the source, for reporting
+    pub name: String,
+
+    /// The path of the note in the source, which links in it are resolved from
+    pub path: PathBuf,
+
+    /// The note's Markdown, with any front matter it came with
+    pub md: String,
+
+    /// The title to add to the front matter when it doesn't already have one
+    pub title: Option<String>,
+
+    /// When the note was created, if the source says
+    pub created: Option<DateTime<FixedOffset>>,
+}
+
+/// A note or attachment that couldn't be imported, or only partly.
+pub struct Problem {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Everything read from the source.
+#[derive(Default)]
+pub struct Import {
+    pub items: Vec<Item>,
+
+    /// Attachments that don't exist as files in the source, by the path links to them resolve to
+    pub attachments: HashMap<PathBuf, Vec<u8>>,
+
+    pub problems: Vec<Problem>,
+}
+
+impl Import {
+    fn problem(&mut self, name: impl Into<String>, reason: impl ToString) {
+        self.problems.push(Problem {
+            name: name.into(),
+            reason: reason.to_string(),
+        });
+    }
+}
+
+/// Read an Obsidian vault, where a note's title is its filename. Wiki links to attachments, such
+/// as `![[diagram.png]]`, become Markdown links so that they still work once the notes move.
+pub fn obsidian(dir: &Path, timezone: Timezone) -> Result<Import> {
+    let files = files(dir)?;
+    let mut by_name = HashMap::<String, Vec<&PathBuf>>::new();
+    for file in &files {
+        if let Some(name) = file.file_name() {
+            by_name
+                .entry(name.to_string_lossy().to_lowercase())
+                .or_default()
+                .push(file);
+        }
+    }
+
+    let mut import = Import::default();
+    for path in files.iter().filter(|path| is_md(path)) {
+        let name = display(dir, path);
+        let md = match read(path) {
+            Ok(md) => md,
+            Err(err) => {
+                import.problem(name, err);
+                continue;
+            }
+        };
+
+        // Attachments are linked by their filename, or their path from the vault when ambiguous
+        let md = links::parse(&md)
+            .into_iter()
+            .rev()
+            .fold(md.clone(), |md, link| {
+                let target = link.target.trim();
+                if link.kind != Kind::Wiki || target.ends_with(".md") || !target.contains('.') {
+                    return md;
+                }
+                let found = match target.contains('/') {
+                    true => Some(dir.join(target)).filter(|file| file.is_file()),
+                    false => match by_name.get(&target.to_lowercase()).map(Vec::as_slice) {
+                        Some([file]) => Some(file.to_path_buf()),
+                        _ => None,
+                    },
+                };
+                match found {
+                    Some(file) => embed(&md, &link, path, &file),
+                    None => {
+                        import.problem(&name, format!("Attachment {} not found", target));
+                        md
+                    }
+                }
+            });
+
+        import.items.push(Item {
+            title: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned()),
+            created: created(&md, path, timezone),
+            name,
+            path: path.clone(),
+            md,
+        });
+    }
+
+    Ok(import)
+}
+
+/// Read a directory of Markdown files with front matter, such as a Joplin export.
+pub fn markdown(dir: &Path, timezone: Timezone) -> Result<Import> {
+    read_files(dir, timezone, false)
+}
+
+/// Read a directory of plain `.txt` and `.md` files.
+pub fn plain(dir: &Path, timezone: Timezone) -> Result<Import> {
+    read_files(dir, timezone, true)
+}
+
+fn read_files(dir: &Path, timezone: Timezone, text: bool) -> Result<Import> {
+    let mut import = Import::default();
+    for path in files(dir)? {
+        let is_text = path.extension().is_some_and(|ext| ext == "txt");
+        if !(is_md(&path) || text && is_text) {
+            continue;
+        }
+
+        let name = display(dir, &path);
+        match read(&path) {
+            Ok(md) => import.items.push(Item {
+                title: note::title(&md, Some(&path)),
+                created: created(&md, &path, timezone),
+                name,
+                // Text files are imported as notes, so links are resolved as if they already were
+                path: path.with_extension("md"),
+                md,
+            }),
+            Err(err) => import.problem(name, err),
+        }
+    }
+
+    Ok(import)
+}
+
+/// Read a note, checking its front matter so that a note that can't be read is never imported.
+fn read(path: &Path) -> Result<String> {
+    let md = fs::read_to_string(path).context("Unable to read the file as UTF-8 text")?;
+    frontmatter::parse(&md)?;
+
+    Ok(md)
+}
+
+/// When the note was created, from its front matter or otherwise the file.
+fn created(md: &str, path: &Path, timezone: Timezone) -> Option<DateTime<FixedOffset>> {
+    let front_matter = frontmatter::parse(md).unwrap_or_default();
+    CREATED_KEYS
+        .iter()
+        .find_map(|key| parse_date(&frontmatter::strings(&front_matter, key).pop()?, timezone))
+        .or_else(|| {
+            let metadata = fs::metadata(path).ok()?;
+            let time = metadata.created().or_else(|_| metadata.modified()).ok()?;
+            Some(timezone.convert(DateTime::<chrono::Utc>::from(time)))
+        })
+}
+
+/// Read a date as written by other apps, with or without a time and offset. Times without an
+/// offset are in the timezone.
+fn parse_date(date: &str, timezone: Timezone) -> Option<DateTime<FixedOffset>> {
+    let date = date.trim();
+    let timestamp = DateTime::parse_from_rfc3339(date)
+        .or_else(|_| DateTime::parse_from_rfc3339(&date.replacen(' ', "T", 1)))
+        .ok();
+    if let Some(timestamp) = timestamp {
+        return Some(timezone.convert(timestamp));
+    }
+
+    let local = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(date, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })?;
+    timezone.at(&local)
+}
+
+/// Replace a wiki link with a Markdown link to the file, keeping any `!` that embeds it.
+fn embed(md: &str, link: &links::Link, from: &Path, file: &Path) -> String {
+    let Some(close) = md[link.range.start..].find("]]") else {
+        return md.to_string();
+    };
+    let start = link.range.start - 2;
+    let end = link.range.start + close + 2;
+
+    // Obsidian uses the label of an embedded image for its size, which is no use as alt text
+    let name = file
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let label = md[link.range.end..end - 2]
+        .strip_prefix('|')
+        .filter(|label| !label.chars().all(|c| c.is_ascii_digit() || c == 'x'))
+        .unwrap_or(&name);
+    let dir = from.parent().unwrap_or(Path::new(""));
+    let target = links::relative(dir, file)
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    format!("{}[{}](<{}>){}", &md[..start], label, target, &md[end..])
+}
+
+/// Every file in the directory and below, leaving out hidden files and folders such as
+/// `.obsidian`, in a stable order.
+fn files(dir: &Path) -> Result<Vec<PathBuf>> {
+    fn visit(dir: &Path, found: &mut Vec<PathBuf>) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            match path.is_dir() {
+                true => visit(&path, found)?,
+                false => found.push(path),
+            }
+        }
+        Ok(())
+    }
+
+    let mut found = Vec::new();
+    visit(dir, &mut found)
+        .with_context(|| format!("Unable to read directory {}", dir.to_string_lossy()))?;
+    found.sort();
+
+    Ok(found)
+}
+
+fn is_md(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "md")
+}
+
+/// The path of a file in the source, as shown in the report.
+fn display(dir: &Path, path: &Path) -> String {
+    path.strip_prefix(dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
@@ -0,0 +1,580 @@
+//! Finding, resolving, and rewriting the links between notes.
+//!
+//! Two kinds of link are understood: wiki links such as `[[note]]`, `[[folder/note|label]]`, or
+//! `![[note#heading]]`, which name a note by its filename, its path from the notes root, or its
+//! title or one of its aliases, and
+//! Markdown links and reference definitions with a relative destination, such as
+//! `[label](../note.md#heading)`, which are relative to the linking note. Links inside code are
+//! ignored.
+
+use std::{
+    fs,
+    ops::Range,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{frontmatter, note};
+
+/// The kinds of link found in a note.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Wiki,
+    Markdown,
+}
+
+/// A link in a note, with the byte range of its target, excluding any heading or label.
+#[derive(Debug, Clone)]
+pub struct Link {
+    pub kind: Kind,
+    pub range: Range<usize>,
+    pub target: String,
+
+    /// Whether a Markdown destination is wrapped in angle brackets, so it can contain spaces
+    pub bracketed: bool,
+}
+
+/// Every note under the notes root, used to resolve wiki links by name.
+#[derive(Clone)]
+pub struct Index {
+    root: PathBuf,
+    notes: Vec<PathBuf>,
+
+    /// The lowercased title and aliases declared in each note, in the same order as the notes
+    names: Vec<Vec<String>>,
+}
+
+impl Index {
+    /// Index the notes, which must be absolute paths under the root, reading their titles and
+    /// aliases. Notes that can't be read are only known by their paths.
+    pub fn new(root: &Path, notes: Vec<PathBuf>) -> Self {
+        let names = notes
+            .iter()
+            .map(|note| fs::read_to_string(note).map_or_else(|_| Vec::new(), |md| names(&md)))
+            .collect();
+        Self {
+            root: root.to_path_buf(),
+            notes,
+            names,
+        }
+    }
+
+    /// The index after the note is moved, known by the same title and aliases as before.
+    pub fn moved(&self, from: &Path, to: &Path) -> Self {
+        let mut index = self.clone();
+        for note in &mut index.notes {
+            if note == from {
+                *note = to.to_path_buf();
+            }
+        }
+        index
+    }
+
+    pub fn notes(&self) -> &[PathBuf] {
+        &self.notes
+    }
+
+    /// The path the link in the given note points to, if it is a link to a local file.
+    ///
+    /// Markdown links resolve whether or not the file exists. Wiki links resolve to the note with
+    /// the given path from the root, or the only note with the given filename, and failing that
+    /// the only note with the given title or alias, as found by [`note::declared_title`].
+    pub fn resolve(&self, link: &Link, from: &Path) -> Option<PathBuf> {
+        match link.kind {
+            Kind::Markdown => {
+                let dir = from.parent()?;
+                Some(normalize(&dir.join(decode(&link.target))))
+            }
+            Kind::Wiki => {
+                let name = link.target.trim().to_lowercase();
+                let target = name.strip_suffix(".md").unwrap_or(&name);
+                let by_path = self.notes.iter().filter(|note| match target.contains('/') {
+                    true => self.wiki_path(note).to_lowercase() == target,
+                    false => stem(note).to_lowercase() == target,
+                });
+                let by_name = self
+                    .notes
+                    .iter()
+                    .zip(&self.names)
+                    .filter(|(_, names)| names.contains(&name))
+                    .map(|(note, _)| note);
+                only(by_path).or_else(|| only(by_name))
+            }
+        }
+    }
+
+    /// The target to use in a link to the note, in the same style as the original link.
+    ///
+    /// Wiki links keep to the filename where that is unambiguous, and otherwise use the path from
+    /// the root. Markdown links are relative to the linking note.
+    pub fn target(&self, link: &Link, from: &Path, to: &Path) -> String {
+        match link.kind {
+            Kind::Markdown => {
+                let dir = from.parent().unwrap_or(Path::new(""));
+                let path = relative(dir, to).to_string_lossy().replace('\\', "/");
+                // Spaces must be encoded unless the destination is in angle brackets
+                match !link.bracketed && path.contains(' ') {
+                    true => path.replace(' ', "%20"),
+                    false => path,
+                }
+            }
+            Kind::Wiki => {
+                let name = stem(to);
+                let unique = self
+                    .notes
+                    .iter()
+                    .filter(|note| stem(note).eq_ignore_ascii_case(&name))
+                    .count()
+                    <= 1;
+                let mut target = match link.target.contains('/') || !unique {
+                    true => self.wiki_path(to),
+                    false => name,
+                };
+                if link.target.trim().ends_with(".md") {
+                    target.push_str(".md");
+                }
+                target
+            }
+        }
+    }
+
+    /// The path of the note from the root without its extension, as used in wiki links.
+    fn wiki_path(&self, note: &Path) -> String {
+        let path = note.strip_prefix(&self.root).unwrap_or(note);
+        path.with_extension("").to_string_lossy().replace('\\', "/")
+    }
+}
+
+/// The lowercased title and aliases declared in the note.
+fn names(md: &str) -> Vec<String> {
+    let front_matter = frontmatter::parse(md).unwrap_or_default();
+    note::declared_title(md)
+        .into_iter()
+        .chain(frontmatter::strings(&front_matter, "aliases"))
+        .map(|name| name.trim().to_lowercase())
+        .collect()
+}
+
+/// The only note found, if there is exactly one.
+fn only<'a>(mut found: impl Iterator<Item = &'a PathBuf>) -> Option<PathBuf> {
+    match (found.next(), found.next()) {
+        (Some(note), None) => Some(note.clone()),
+        _ => None,
+    }
+}
+
+/// Find every link in the markdown.
+pub fn parse(md: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut offset = 0;
+
+    for line in md.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        // Skip fenced code blocks, which close with the same fence they opened with
+        let trimmed = line.trim();
+        if let Some(open) = fence {
+            if trimmed.starts_with(open) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(open) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+            fence = Some(open);
+            continue;
+        }
+
+        // Reference definitions, e.g. [label]: path.md
+        if let Some(link) = definition(line) {
+            links.push(shift(link, start));
+            continue;
+        }
+
+        // Only look outside inline code, which sits between pairs of backticks
+        let mut code = false;
+        let mut pos = 0;
+        for segment in line.split('`') {
+            if !code {
+                links.extend(
+                    wiki(segment)
+                        .into_iter()
+                        .map(|link| shift(link, start + pos)),
+                );
+                links.extend(
+                    inline(segment)
+                        .into_iter()
+                        .map(|link| shift(link, start + pos)),
+                );
+            }
+            code = !code;
+            pos += segment.len() + 1;
+        }
+    }
+
+    links.sort_by_key(|link| link.range.start);
+    links
+}
+
+/// Replace the target of each link the function returns a new target for.
+pub fn rewrite(md: &str, mut replace: impl FnMut(&Link) -> Option<String>) -> String {
+    let mut result = String::with_capacity(md.len());
+    let mut last = 0;
+    for link in parse(md) {
+        if let Some(target) = replace(&link) {
+            result.push_str(&md[last..link.range.start]);
+            result.push_str(&target);
+            last = link.range.end;
+        }
+    }
+    result.push_str(&md[last..]);
+
+    result
+}
+
+/// The notes in the index with links pointing at the target.
+pub fn incoming(index: &Index, target: &Path) -> Vec<PathBuf> {
+    index
+        .notes()
+        .iter()
+        .filter(|note| note.as_path() != target)
+        .filter(|note| {
+            fs::read_to_string(note).is_ok_and(|md| {
+                parse(&md)
+                    .iter()
+                    .any(|link| index.resolve(link, note).as_deref() == Some(target))
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Warn that links in other notes will break when the note is moved away.
+pub fn warn_incoming(index: &Index, note: &Path) {
+    let incoming = incoming(index, note);
+    if incoming.is_empty() {
+        return;
+    }
+
+    eprintln!(
+        "Warning: {} {} to {}:",
+        incoming.len(),
+        if incoming.len() == 1 {
+            "note links"
+        } else {
+            "notes link"
+        },
+        note.to_string_lossy()
+    );
+    for path in incoming {
+        eprintln!("  {}", path.to_string_lossy());
+    }
+}
+
+fn shift(mut link: Link, by: usize) -> Link {
+    link.range = link.range.start + by..link.range.end + by;
+    link
+}
+
+/// Wiki links in a span of text.
+fn wiki(text: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut pos = 0;
+    while let Some(open) = text[pos..].find("[[") {
+        let start = pos + open + 2;
+        let Some(close) = text[start..].find("]]") else {
+            break;
+        };
+        let inner = &text[start..start + close];
+        let end = start + inner.find(['|', '#']).unwrap_or(inner.len());
+        if !inner.contains('[') && end > start {
+            links.push(Link {
+                kind: Kind::Wiki,
+                range: start..end,
+                target: text[start..end].to_string(),
+                bracketed: false,
+            });
+        }
+        pos = start + close + 2;
+    }
+
+    links
+}
+
+/// Inline Markdown links and images in a span of text.
+fn inline(text: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut pos = 0;
+    while let Some(open) = text[pos..].find("](") {
+        let start = pos + open + 2;
+        pos = start;
+        if let Some(link) = destination(text, start) {
+            pos = link.range.end;
+            links.push(link);
+        }
+    }
+
+    links
+}
+
+/// A reference definition taking up the whole line.
+fn definition(line: &str) -> Option<Link> {
+    let indent = line.len() - line.trim_start().len();
+    if indent > 3 || !line[indent..].starts_with('[') || line[indent..].starts_with("[[") {
+        return None;
+    }
+    let colon = line.find("]:")? + 2;
+    let start = colon + line[colon..].len() - line[colon..].trim_start().len();
+    destination(line, start)
+}
+
+/// Parse a link destination starting at the given position, if it is a relative path.
+fn destination(text: &str, start: usize) -> Option<Link> {
+    let rest = &text[start..];
+    let (start, dest, bracketed) = match rest.strip_prefix('<') {
+        Some(inner) => (start + 1, &inner[..inner.find('>')?], true),
+        None => {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || c == ')')
+                .unwrap_or(rest.len());
+            (start, &rest[..end], false)
+        }
+    };
+    let path = &dest[..dest.find('#').unwrap_or(dest.len())];
+
+    if path.is_empty() || path.starts_with('/') || is_url(path) {
+        return None;
+    }
+    Some(Link {
+        kind: Kind::Markdown,
+        range: start..start + path.len(),
+        target: path.to_string(),
+        bracketed,
+    })
+}
+
+/// Whether the destination starts with a URL scheme, such as `https:` or `mailto:`.
+fn is_url(dest: &str) -> bool {
+    match dest.find(':') {
+        Some(colon) => dest[..colon]
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')),
+        None => false,
+    }
+}
+
+/// Decode percent-encoded characters in a link destination.
+fn decode(dest: &str) -> String {
+    let bytes = dest.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Resolve `.` and `..` in a path without touching the filesystem.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normal.pop();
+            }
+            other => normal.push(other),
+        }
+    }
+
+    normal
+}
+
+/// The path to `to` relative to the directory `from`, both absolute.
+pub fn relative(from: &Path, to: &Path) -> PathBuf {
+    let from = from.components().collect::<Vec<_>>();
+    let to = to.components().collect::<Vec<_>>();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    (common..from.len())
+        .map(|_| Component::ParentDir)
+        .chain(to[common..].iter().copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The kind and target of each link found.
+    fn targets(md: &str) -> Vec<(Kind, &str)> {
+        parse(md)
+            .into_iter()
+            .map(|link| {
+                assert_eq!(&md[link.range.clone()], link.target);
+                (link.kind, &md[link.range])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_wiki_links() {
+        assert_eq!(
+            targets("[[note]], [[folder/other|label]] and ![[image.png#part]]"),
+            [
+                (Kind::Wiki, "note"),
+                (Kind::Wiki, "folder/other"),
+                (Kind::Wiki, "image.png"),
+            ]
+        );
+        assert!(targets("[[]] [[#heading]] [[unclosed").is_empty());
+    }
+
+    #[test]
+    fn finds_relative_markdown_links() {
+        assert_eq!(
+            targets("[a](../a.md#top) ![b](<img/with space.png> \"title\") [c](c.md)"),
+            [
+                (Kind::Markdown, "../a.md"),
+                (Kind::Markdown, "img/with space.png"),
+                (Kind::Markdown, "c.md"),
+            ]
+        );
+        assert!(targets(
+            "[web](https://example.com) [mail](mailto:a@b.c) [abs](/a.md) [here](#top)"
+        )
+        .is_empty());
+
+        let links = parse("[a](<b c.md>)");
+        assert!(links[0].bracketed);
+    }
+
+    #[test]
+    fn finds_reference_definitions() {
+        assert_eq!(
+            targets("[label]: notes/a.md\n   [other]:   <b.md>\n    [code]: c.md\n"),
+            [(Kind::Markdown, "notes/a.md"), (Kind::Markdown, "b.md")]
+        );
+    }
+
+    #[test]
+    fn skips_code() {
+        let md = "`[[inline]]` [[after]]\n```\n[[fenced]]\n~~~\n[x](still.md)\n```\n~~~\n[[tilde]]\n~~~\n[[last]]\n";
+        assert_eq!(targets(md), [(Kind::Wiki, "after"), (Kind::Wiki, "last")]);
+    }
+
+    #[test]
+    fn rewrites_only_replaced_targets() {
+        let md = "[[a|A]] and [b](b.md#x) and [[c]]\n";
+        let rewritten = rewrite(md, |link| match link.target.as_str() {
+            "a" => Some("folder/a".to_string()),
+            "b.md" => Some("../b.md".to_string()),
+            _ => None,
+        });
+        assert_eq!(rewritten, "[[folder/a|A]] and [b](../b.md#x) and [[c]]\n");
+    }
+
+    #[test]
+    fn resolves_and_targets_links() {
+        let root = Path::new("/notes");
+        let index = Index::new(
+            root,
+            vec![
+                PathBuf::from("/notes/a/plan.md"),
+                PathBuf::from("/notes/b/plan.md"),
+                PathBuf::from("/notes/b/My Note.md"),
+            ],
+        );
+        let from = Path::new("/notes/a/plan.md");
+        let link = |md: &str| parse(md).remove(0);
+
+        assert_eq!(index.resolve(&link("[[plan]]"), from), None);
+        assert_eq!(
+            index.resolve(&link("[[b/Plan]]"), from),
+            Some(PathBuf::from("/notes/b/plan.md"))
+        );
+        assert_eq!(
+            index.resolve(&link("[[my note.md]]"), from),
+            Some(PathBuf::from("/notes/b/My Note.md"))
+        );
+        assert_eq!(
+            index.resolve(&link("[x](../b/My%20Note.md)"), from),
+            Some(PathBuf::from("/notes/b/My Note.md"))
+        );
+
+        let to = Path::new("/notes/b/My Note.md");
+        assert_eq!(index.target(&link("[[x]]"), from, to), "My Note");
+        assert_eq!(index.target(&link("[[x.md]]"), from, to), "My Note.md");
+        assert_eq!(
+            index.target(&link("[[x]]"), from, Path::new("/notes/b/plan.md")),
+            "b/plan"
+        );
+        assert_eq!(
+            index.target(&link("[x](x.md)"), from, to),
+            "../b/My%20Note.md"
+        );
+        assert_eq!(
+            index.target(&link("[x](<x.md>)"), from, to),
+            "../b/My Note.md"
+        );
+    }
+
+    #[test]
+    fn resolves_wiki_links_by_title_and_alias() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let write = |name: &str, md: &str| {
+            let path = root.join(name);
+            fs::write(&path, md).unwrap();
+            path
+        };
+        let atom = write(
+            "20240101_120000.md",
+            "---\ntitle: Weekly Plan\naliases: [plan, roadmap]\n---\n\n# Weekly Plan\n",
+        );
+        let headed = write("20240102_120000.md", "# Reading list\n");
+        let named = write("roadmap.md", "# Other\n");
+        let index = Index::new(root, vec![atom.clone(), headed.clone(), named.clone()]);
+        let link = |md: &str| parse(md).remove(0);
+
+        assert_eq!(
+            index.resolve(&link("[[weekly plan]]"), &named),
+            Some(atom.clone())
+        );
+        assert_eq!(
+            index.resolve(&link("[[Plan|the plan]]"), &named),
+            Some(atom)
+        );
+        assert_eq!(
+            index.resolve(&link("[[Reading List]]"), &named),
+            Some(headed)
+        );
+
+        // Filenames come before titles and aliases
+        assert_eq!(
+            index.resolve(&link("[[roadmap]]"), &named),
+            Some(named.clone())
+        );
+        assert_eq!(index.resolve(&link("[[other]]"), &named), Some(named));
+    }
+}
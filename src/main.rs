@@ -6,25 +6,78 @@
 //! - [Note-Taking in Vanilla Vim](https://www.edwinwenink.xyz/posts/42-vim_notetaking/)
 
 mod args;
+mod bulk;
 mod commands;
 mod config;
+mod editor;
+mod export;
+mod frontmatter;
+mod git;
+mod hooks;
 mod html;
+mod import;
+mod links;
+mod note;
 mod path;
+mod picker;
+mod query;
+mod tasks;
+mod theme;
+mod trash;
+
+use std::process;
 
 use anyhow::Result;
 use clap::Parser;
 
 use crate::args::{Args, Subcommand};
 use crate::config::Config;
+use crate::editor::EditorError;
 
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let result = run(Args::parse());
+
+    // Exit with the editor's own status when it fails, so scripts wrapping jot can see it
+    if let Err(err) = &result {
+        if let Some(EditorError {
+            code: Some(code), ..
+        }) = err.downcast_ref::<EditorError>()
+        {
+            eprintln!("Error: {:?}", err);
+            process::exit(*code);
+        }
+    }
+
+    result
+}
+
+fn run(args: Args) -> Result<()> {
+    // Managing the config must not require a valid config in the first place
+    if let Some(Subcommand::Config(cmd)) = &args.subcommand {
+        return commands::configure(cmd, &args);
+    }
     let config = Config::try_new(&args)?;
 
     // Route based on the command
     match args.subcommand {
         Some(Subcommand::Html(args)) => commands::render_html(&args, &config),
         Some(Subcommand::Pdf(args)) => commands::render_pdf(&args, &config),
+        Some(Subcommand::Edit(args)) => commands::edit(&args, &config),
+        Some(Subcommand::Mv(args)) => commands::mv(&args, &config),
+        Some(Subcommand::Rm(args)) => commands::rm(&args, &config),
+        Some(Subcommand::Trash(args)) => commands::trash(&args, &config),
+        Some(Subcommand::Archive(args)) => commands::archive(&args, &config),
+        Some(Subcommand::Tasks(args)) => commands::tasks(&args, &config),
+        Some(Subcommand::Review(args)) => commands::review(&args, &config),
+        Some(Subcommand::Stats(args)) => commands::stats(&args, &config),
+        Some(Subcommand::Query(args)) => commands::query(&args, &config),
+        Some(Subcommand::Meta(args)) => commands::meta(&args, &config),
+        Some(Subcommand::Migrate(args)) => commands::migrate(&args, &config),
+        Some(Subcommand::Import(args)) => commands::import(&args, &config),
+        Some(Subcommand::Export(args)) => commands::export(&args, &config),
+        Some(Subcommand::Commit(args)) => commands::commit(&args, &config),
+        Some(Subcommand::Sync(args)) => commands::sync(&args, &config),
+        Some(Subcommand::Config(_)) => unreachable!("Config is handled before loading config"),
         None => commands::create(&args, &config),
     }
 }
@@ -0,0 +1,223 @@
+//! Finding and reading the notes under the notes root.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::{
+    config::{Create, Timezone},
+    frontmatter,
+};
+
+/// A note along with the metadata used to find it.
+#[derive(Debug)]
+pub struct Note {
+    pub path: PathBuf,
+
+    /// The file stem, which for atoms is the timestamp they were created at
+    pub id: String,
+
+    /// The title, see [`title`]
+    pub title: String,
+
+    /// Other names for the note from the front matter
+    pub aliases: Vec<String>,
+}
+
+impl Note {
+    /// Read the note at the given path.
+    ///
+    /// Front matter that can't be parsed is treated as missing, so one broken note doesn't stop
+    /// the others from being found.
+    pub fn read(path: &Path) -> Result<Self> {
+        let md = fs::read_to_string(path)
+            .with_context(|| format!("Unable to read note {}", path.to_string_lossy()))?;
+        let front_matter = frontmatter::parse(&md).unwrap_or_default();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            id: stem(path),
+            title: title(&md, Some(path)).unwrap_or_default(),
+            aliases: frontmatter::strings(&front_matter, "aliases"),
+        })
+    }
+}
+
+/// Every markdown file under the directory, sorted by path.
+///
+/// Hidden files and directories, such as `.git`, are skipped.
+pub fn walk(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    visit(dir, &mut found)
+        .with_context(|| format!("Unable to list notes in {}", dir.to_string_lossy()))?;
+    found.sort();
+
+    Ok(found)
+}
+
+fn visit(dir: &Path, found: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            visit(&path, found)?;
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            found.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// The note under the directory that was most recently created or modified.
+pub fn latest(dir: &Path) -> Result<Option<PathBuf>> {
+    let changed = |path: &PathBuf| {
+        fs::metadata(path)
+            .map(|meta| {
+                let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                meta.created()
+                    .map_or(modified, |created| created.max(modified))
+            })
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    };
+
+    Ok(walk(dir)?.into_iter().max_by_key(changed))
+}
+
+/// The title of a note: the `title` front matter, then the first heading, then the filename.
+///
+/// Returns None only when there is no title in the markdown and no path to fall back on, such as
+/// when reading from stdin.
+pub fn title(md: &str, path: Option<&Path>) -> Option<String> {
+    declared_title(md).or_else(|| path.map(stem))
+}
+
+/// The title given in the note itself, from the `title` front matter or the first heading.
+pub fn declared_title(md: &str) -> Option<String> {
+    frontmatter::parse(md)
+        .ok()
+        .and_then(|front_matter| frontmatter::strings(&front_matter, "title").pop())
+        .filter(|title| !title.trim().is_empty())
+        .or_else(|| heading(frontmatter::split(md).1))
+}
+
+fn stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// The text of the first heading in the markdown, of any level.
+fn heading(md: &str) -> Option<String> {
+    md.lines()
+        .map(str::trim_start)
+        .filter(|line| line.starts_with('#'))
+        .map(|line| line.trim_start_matches('#'))
+        // A space is needed after the hashes so that tags aren't mistaken for headings
+        .find(|text| text.starts_with(' '))
+        .map(|text| text.trim().to_string())
+        .filter(|title| !title.is_empty())
+}
+
+/// The tags of a note, from the `tags` front matter and `#tags` in the body, lowercased and without
+/// the leading #.
+pub fn tags(md: &str) -> Vec<String> {
+    let front_matter = frontmatter::parse(md).unwrap_or_default();
+    let mut tags = frontmatter::strings(&front_matter, "tags")
+        .into_iter()
+        .map(|tag| tag.trim_start_matches('#').to_lowercase())
+        .chain(prose(md).flat_map(|(_, line)| inline_tags(line)))
+        .filter(|tag| !tag.is_empty())
+        .collect::<Vec<_>>();
+    tags.sort();
+    tags.dedup();
+
+    tags
+}
+
+/// The `#tags` in a span of text, lowercased and without the leading #.
+///
+/// A tag must follow whitespace or start the text, and can't be only digits so that issue numbers
+/// like #12 aren't counted.
+pub fn inline_tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut prev = ' ';
+    for (i, c) in text.char_indices() {
+        if c == '#' && prev.is_whitespace() {
+            let rest = &text[i + 1..];
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '/')))
+                .unwrap_or(rest.len());
+            let tag = rest[..end].trim_end_matches(['/', '-']);
+            if tag.chars().any(|c| !c.is_ascii_digit()) {
+                tags.push(tag.to_lowercase());
+            }
+        }
+        prev = c;
+    }
+
+    tags
+}
+
+/// The lines of a note outside its front matter and fenced code blocks, with their line numbers
+/// counting from 1.
+pub fn prose(md: &str) -> impl Iterator<Item = (usize, &str)> {
+    let body = frontmatter::split(md).1;
+    let skipped = md[..md.len() - body.len()].lines().count();
+    let mut fence: Option<&str> = None;
+
+    body.lines()
+        .enumerate()
+        .filter(move |(_, line)| {
+            // Fenced code blocks close with the same fence they opened with
+            let trimmed = line.trim();
+            if let Some(open) = fence {
+                if trimmed.starts_with(open) {
+                    fence = None;
+                }
+                return false;
+            }
+            if let Some(open) = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f)) {
+                fence = Some(open);
+                return false;
+            }
+            true
+        })
+        .map(move |(i, line)| (skipped + i + 1, line))
+}
+
+/// The date a note was created in the timezone new notes are dated in, from its `timestamp` front
+/// matter, or else the atom ID it is named with.
+pub fn created(md: &str, path: &Path, create: &Create) -> Option<NaiveDate> {
+    let front_matter = frontmatter::parse(md).unwrap_or_default();
+    frontmatter::strings(&front_matter, "timestamp")
+        .pop()
+        .and_then(|timestamp| create.parse_timestamp(&timestamp))
+        .map(|timestamp| create.timezone.convert(timestamp).date_naive())
+        .or_else(|| id_date(path))
+}
+
+/// The date in an atom's ID, which starts with the date it was created as `%Y%m%d`.
+pub fn id_date(path: &Path) -> Option<NaiveDate> {
+    let stem = stem(path);
+    NaiveDate::parse_from_str(stem.get(..8)?, "%Y%m%d").ok()
+}
+
+/// The date the note's file was last modified in the timezone.
+pub fn modified(path: &Path, timezone: Timezone) -> Option<NaiveDate> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(
+        timezone
+            .convert(DateTime::<Utc>::from(modified))
+            .date_naive(),
+    )
+}
@@ -1,19 +1,23 @@
 //! Path helpers.
 
 use std::{
+    env,
     ffi::OsString,
+    fs::{self, File, OpenOptions},
     io::{self, Read},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 use anyhow::{anyhow, bail, Context, Result};
 
+use crate::links::normalize;
+
 /// Take the output path provided, check its validity, and canonicalize.
 /// If the original output is none, we put the file next to its .md source, otherwise try to canonicalize the file name.
 pub(crate) fn generate_output_path(
     ext: &str,
     original_output: Option<PathBuf>,
-    input: &PathBuf,
+    input: &Path,
 ) -> Result<PathBuf> {
     // Replace the original output option with the input folder
     // This *should* be valid, but we error appropriately anyway because we are dealing with IO
@@ -88,3 +92,128 @@ pub(crate) fn read_md_from_stdin() -> Result<String> {
     }
     Ok(md)
 }
+
+/// Pulls a list of paths from stdin, one per line, skipping blank lines.
+pub(crate) fn read_paths_from_stdin() -> Result<Vec<PathBuf>> {
+    Ok(io::read_to_string(io::stdin())
+        .context("Unable to read paths from stdin")?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Check that several rendered notes can be written to the output, which must be a directory or
+/// left out to write each next to its note.
+pub(crate) fn check_outputs(output: &Option<Option<PathBuf>>, count: usize) -> Result<()> {
+    match output {
+        Some(Some(output)) if count > 1 && !output.is_dir() => bail!(
+            "Unable to render {} notes to the single file {}, give a directory instead",
+            count,
+            output.to_string_lossy()
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Exclusively create a new file at the given path, never clobbering an existing file.
+///
+/// If the path is already taken, a numeric suffix is appended to the file stem (`_1`, `_2`, ...) until a free name
+/// is found. The check and the create are a single atomic operation, so concurrent callers can never end up with
+/// the same file. Returns the open file handle along with the path that was actually created.
+pub(crate) fn create_unique(path: &Path) -> Result<(File, PathBuf)> {
+    unique(path, |candidate| {
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(candidate)
+    })
+}
+
+/// Create a new, empty directory at the given path, adding a numeric suffix like [`create_unique`]
+/// if the path is already taken. The parent directory is created if needed. Returns the path that
+/// was actually created.
+pub(crate) fn create_unique_dir(path: &Path) -> Result<PathBuf> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Unable to create directory {}", parent.to_string_lossy()))?;
+    }
+
+    Ok(unique(path, |candidate| fs::create_dir(candidate))?.1)
+}
+
+/// Create something at the first free path of the given path and its suffixed alternatives, with
+/// the create function failing if the path is already taken.
+fn unique<T>(path: &Path, create: impl Fn(&Path) -> io::Result<T>) -> Result<(T, PathBuf)> {
+    const MAX_ATTEMPTS: usize = 1000;
+
+    let stem = path
+        .file_stem()
+        .with_context(|| format!("Path does not have a filename: {}", path.to_string_lossy()))?
+        .to_string_lossy()
+        .into_owned();
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().into_owned());
+
+    for attempt in 0..MAX_ATTEMPTS {
+        let candidate = match attempt {
+            0 => path.to_path_buf(),
+            n => {
+                let mut candidate = path.with_file_name(format!("{}_{}", stem, n));
+                if let Some(ext) = &ext {
+                    candidate.set_extension(ext);
+                }
+                candidate
+            }
+        };
+
+        match create(&candidate) {
+            Ok(created) => return Ok((created, candidate)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("Unable to create {}", candidate.to_string_lossy()))
+            }
+        }
+    }
+
+    bail!(
+        "Unable to find a free name for {} after {} attempts",
+        path.to_string_lossy(),
+        MAX_ATTEMPTS
+    )
+}
+
+/// Where a note path given on the command line points. Relative paths are relative to the current
+/// directory when it is inside the notes root, and to the root otherwise.
+pub(crate) fn locate(path: &Path, root: &Path) -> Result<PathBuf> {
+    let cwd = env::current_dir()?.canonicalize()?;
+    let base = match cwd.starts_with(root) {
+        true => cwd,
+        false => root.to_path_buf(),
+    };
+
+    Ok(normalize(&base.join(path)))
+}
+
+/// Find an existing note from a path given on the command line as with [`locate`]. The note must
+/// be a markdown file inside the notes root, which must be canonical.
+pub(crate) fn locate_note(path: &Path, root: &Path) -> Result<PathBuf> {
+    let note = locate(path, root)?
+        .canonicalize()
+        .with_context(|| format!("Note {} not found", path.to_string_lossy()))?;
+    if !note.is_file() || note.extension().is_none_or(|ext| ext != "md") {
+        bail!("{} is not a markdown note", note.to_string_lossy());
+    }
+    if !note.starts_with(root) {
+        bail!(
+            "{} is outside the notes root {}",
+            note.to_string_lossy(),
+            root.to_string_lossy()
+        );
+    }
+
+    Ok(note)
+}
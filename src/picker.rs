@@ -0,0 +1,229 @@
+//! A minimal interactive fuzzy picker for choosing between notes in the terminal.
+//!
+//! The picker draws on stderr so that stdout stays free for output that is piped elsewhere.
+
+use std::io::{self, IsTerminal, Stderr, Write};
+
+use anyhow::Result;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, ClearType},
+};
+
+/// Whether there is a terminal to show the picker in.
+pub fn available() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+/// Let the user choose one of the items, filtering as they type. Returns the index of the chosen
+/// item, or None if the picker was cancelled.
+pub fn pick(items: &[String]) -> Result<Option<usize>> {
+    let mut screen = Screen::enter()?;
+    let mut query = String::new();
+    let mut selected = 0;
+
+    loop {
+        let matches = filter(&query, items);
+        selected = selected.min(matches.len().saturating_sub(1));
+        screen.draw(&query, &matches, items, selected)?;
+
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+
+        match code {
+            KeyCode::Enter => return Ok(matches.get(selected).copied()),
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') if ctrl => return Ok(None),
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Char('p' | 'k') if ctrl => selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Tab => selected += 1,
+            KeyCode::Char('n' | 'j') if ctrl => selected += 1,
+            KeyCode::Char('u') if ctrl => query.clear(),
+            KeyCode::Backspace => {
+                query.pop();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The indices of the items matching the query, best first.
+fn filter(query: &str, items: &[String]) -> Vec<usize> {
+    let mut scored = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| score(query, item).map(|score| (score, i)))
+        .collect::<Vec<_>>();
+    // Stable, so equally good matches keep their order
+    scored.sort_by_key(|(score, _)| -score);
+
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+/// Score how well the text matches the query, ignoring case, or None if it doesn't match at all.
+///
+/// Every character of the query must appear in the text in order. Runs of consecutive characters
+/// and characters at the start of words score more, and gaps between them score less.
+pub fn score(query: &str, text: &str) -> Option<i64> {
+    let text = text
+        .chars()
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last: Option<usize> = None;
+
+    for q in query.chars().flat_map(char::to_lowercase) {
+        if q.is_whitespace() {
+            continue;
+        }
+        let found = next + text[next..].iter().position(|&c| c == q)?;
+
+        score += 1;
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        match last {
+            Some(last) if found == last + 1 => score += 5,
+            Some(last) => score -= (found - last - 1).min(5) as i64,
+            None => {}
+        }
+
+        last = Some(found);
+        next = found + 1;
+    }
+
+    Some(score)
+}
+
+/// The terminal in raw mode on the alternate screen, restored when dropped.
+struct Screen {
+    out: Stderr,
+}
+
+impl Screen {
+    fn enter() -> Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut out = io::stderr();
+        if let Err(err) = execute!(out, terminal::EnterAlternateScreen) {
+            let _ = terminal::disable_raw_mode();
+            return Err(err.into());
+        }
+
+        Ok(Self { out })
+    }
+
+    fn draw(
+        &mut self,
+        query: &str,
+        matches: &[usize],
+        items: &[String],
+        selected: usize,
+    ) -> Result<()> {
+        // Some terminals don't report a size, so assume a typical one
+        let (width, height) = match terminal::size()? {
+            (0, _) | (_, 0) => (80, 24),
+            size => size,
+        };
+        let width = width as usize;
+        let rows = (height as usize).saturating_sub(2);
+
+        // Scroll so the selected item is always visible
+        let first = (selected + 1).saturating_sub(rows);
+
+        queue!(
+            self.out,
+            terminal::Clear(ClearType::All),
+            cursor::MoveTo(0, 0),
+            Print(format!("  {}/{}", matches.len(), items.len())),
+        )?;
+        for (row, &i) in matches.iter().skip(first).take(rows).enumerate() {
+            let line = truncate(&items[i], width.saturating_sub(2));
+            queue!(self.out, cursor::MoveTo(0, row as u16 + 1))?;
+            match first + row == selected {
+                true => queue!(
+                    self.out,
+                    SetAttribute(Attribute::Reverse),
+                    Print(format!("> {}", line)),
+                    SetAttribute(Attribute::Reset),
+                )?,
+                false => queue!(self.out, Print(format!("  {}", line)))?,
+            }
+        }
+        queue!(
+            self.out,
+            cursor::MoveTo(0, height.saturating_sub(1)),
+            Print(format!("> {}", truncate(query, width.saturating_sub(2)))),
+        )?;
+
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(self.out, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_every_character_in_order() {
+        assert!(score("mtg", "Meeting notes").is_some());
+        assert!(score("gtm", "Meeting notes").is_none());
+        assert!(score("xyz", "Meeting notes").is_none());
+        assert_eq!(score("", "Meeting notes"), Some(0));
+    }
+
+    #[test]
+    fn ignores_case_and_spaces() {
+        assert_eq!(score("MEET", "meeting"), score("meet", "MEETING"));
+        assert_eq!(
+            score("meet notes", "Meeting notes"),
+            score("meetnotes", "Meeting notes")
+        );
+    }
+
+    #[test]
+    fn prefers_runs_and_word_starts() {
+        let word = score("notes", "Notes").unwrap();
+        assert_eq!(score("notes", "my notes"), Some(word));
+        let inside = score("notes", "keynotes").unwrap();
+        let scattered = score("notes", "nxoxtxexs").unwrap();
+        assert!(word > inside);
+        assert!(inside > scattered);
+    }
+
+    #[test]
+    fn sorts_matches_best_first() {
+        let items = ["keynotes", "Other", "Notes", "nxoxtxexs"].map(String::from);
+        assert_eq!(filter("notes", &items), [2, 0, 3]);
+
+        let items = ["b", "a", "c"].map(String::from);
+        assert_eq!(filter("", &items), [0, 1, 2]);
+    }
+}
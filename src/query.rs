@@ -0,0 +1,661 @@
+//! A small expression language for filtering notes by their front matter.
+//!
+//! Expressions compare fields with values, such as `status = "draft"`, `timestamp > 2026-01-01`,
+//! or `"rust" in tags`, and combine them with `and`, `or`, `not`, and parentheses. A field on its
+//! own is true when it is set to something other than false or empty. The comparison operators are
+//! `=`, `!=`, `<`, `<=`, `>`, `>=`, `in`, and `contains`, where `a in b` is the same as
+//! `b contains a` and checks for an item in a list or text within text. Unquoted dates such as
+//! `2026-01-01` compare with timestamps by their date in the timezone new notes are dated in, with
+//! timestamps read in the format new notes are written with.
+
+use std::{cmp::Ordering, fmt, path::Path};
+
+use anyhow::{bail, Result};
+use chrono::NaiveDate;
+use serde_yaml::Mapping;
+
+use crate::{config::Create, frontmatter, note};
+
+/// A value from the front matter or the query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    Date(NaiveDate),
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// Convert a YAML value, with mappings kept as their YAML text.
+    pub fn from_yaml(value: &serde_yaml::Value) -> Self {
+        match value {
+            serde_yaml::Value::Null => Value::Null,
+            serde_yaml::Value::Bool(b) => Value::Bool(*b),
+            serde_yaml::Value::Number(n) => n.as_f64().map_or(Value::Null, Value::Number),
+            serde_yaml::Value::String(s) => Value::Text(s.clone()),
+            serde_yaml::Value::Sequence(seq) => {
+                Value::List(seq.iter().map(Self::from_yaml).collect())
+            }
+            serde_yaml::Value::Mapping(_) => Value::Text(
+                serde_yaml::to_string(value)
+                    .unwrap_or_default()
+                    .trim_end()
+                    .to_string(),
+            ),
+            serde_yaml::Value::Tagged(tagged) => Self::from_yaml(&tagged.value),
+        }
+    }
+
+    /// Convert to JSON, with dates as strings.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Null => serde_json::Value::Null,
+            Value::Bool(b) => (*b).into(),
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => (*n as i64).into(),
+            Value::Number(n) => (*n).into(),
+            Value::Text(s) => s.clone().into(),
+            Value::Date(date) => date.to_string().into(),
+            Value::List(list) => list.iter().map(Value::to_json).collect(),
+        }
+    }
+
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Text(s) => !s.is_empty(),
+            Value::List(list) => !list.is_empty(),
+            Value::Number(_) | Value::Date(_) => true,
+        }
+    }
+
+    /// The value as a date, from a date or the date of a timestamp in the timezone new notes are
+    /// dated in.
+    fn as_date(&self, create: &Create) -> Option<NaiveDate> {
+        match self {
+            Value::Date(date) => Some(*date),
+            Value::Text(s) => create
+                .parse_timestamp(s)
+                .map(|timestamp| create.timezone.convert(timestamp).date_naive())
+                .or_else(|| NaiveDate::parse_from_str(s.get(..10)?, "%Y-%m-%d").ok()),
+            _ => None,
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            Value::Text(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Order two values, converting between them where that makes sense, with timestamps compared
+    /// with dates by their date in the timezone. Values that can't be compared, such as a missing
+    /// field, have no order.
+    fn compare(&self, other: &Value, create: &Create) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Null, _) | (_, Value::Null) => None,
+            (Value::Date(_), _) | (_, Value::Date(_)) => {
+                Some(self.as_date(create)?.cmp(&other.as_date(create)?))
+            }
+            (Value::Number(_), _) | (_, Value::Number(_)) => {
+                self.as_number()?.partial_cmp(&other.as_number()?)
+            }
+            (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+            (Value::Text(a), Value::Text(b)) => Some(a.cmp(b)),
+            (Value::List(a), Value::List(b)) => (a == b).then_some(Ordering::Equal),
+            _ => None,
+        }
+    }
+
+    /// Whether the value holds the item, as an item in a list or text within text.
+    fn contains(&self, item: &Value, create: &Create) -> bool {
+        match (self, item) {
+            (Value::List(list), item) => list
+                .iter()
+                .any(|value| value.compare(item, create) == Some(Ordering::Equal)),
+            (Value::Text(text), Value::Text(item)) => text.contains(item.as_str()),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Text(s) => write!(f, "{}", s),
+            Value::Date(date) => write!(f, "{}", date),
+            Value::List(list) => {
+                let items = list.iter().map(Value::to_string).collect::<Vec<_>>();
+                write!(f, "{}", items.join(", "))
+            }
+        }
+    }
+}
+
+/// The fields of a note that queries run against: its front matter along with `path`, `id`,
+/// `title`, and `tags`, which always come from the note itself.
+pub struct Fields<'a> {
+    front_matter: Mapping,
+    path: String,
+    id: String,
+    title: String,
+    tags: Vec<String>,
+
+    /// How timestamps are read, and the timezone they are dated in when compared with dates
+    create: &'a Create,
+}
+
+impl<'a> Fields<'a> {
+    /// Gather the fields of the note, with the path shown relative to the root.
+    pub fn new(md: &str, path: &Path, root: &Path, create: &'a Create) -> Self {
+        Self {
+            front_matter: frontmatter::parse(md).unwrap_or_default(),
+            path: path
+                .strip_prefix(root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .into_owned(),
+            id: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            title: note::title(md, Some(path)).unwrap_or_default(),
+            tags: note::tags(md),
+            create,
+        }
+    }
+
+    /// The value of a field, where nested fields are separated by dots, e.g. `author.name`.
+    pub fn get(&self, field: &str) -> Value {
+        match field {
+            "path" => return Value::Text(self.path.clone()),
+            "id" => return Value::Text(self.id.clone()),
+            "title" => return Value::Text(self.title.clone()),
+            "tags" => return Value::List(self.tags.iter().cloned().map(Value::Text).collect()),
+            _ => {}
+        }
+
+        if let Some(value) = self.front_matter.get(field) {
+            return Value::from_yaml(value);
+        }
+        let mut parts = field.split('.');
+        let mut value = parts.next().and_then(|key| self.front_matter.get(key));
+        for part in parts {
+            value = value.and_then(|value| value.get(part));
+        }
+        value.map_or(Value::Null, Value::from_yaml)
+    }
+}
+
+/// A comparison operator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+    Contains,
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Op::Eq => "=",
+            Op::Ne => "!=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::In => "in",
+            Op::Contains => "contains",
+        })
+    }
+}
+
+/// A parsed query.
+#[derive(Debug)]
+pub enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, Op, Operand),
+    Truthy(Operand),
+}
+
+#[derive(Debug)]
+pub enum Operand {
+    Field(String),
+    Literal(Value),
+}
+
+impl Operand {
+    fn value(&self, fields: &Fields) -> Value {
+        match self {
+            Operand::Field(field) => fields.get(field),
+            Operand::Literal(value) => value.clone(),
+        }
+    }
+}
+
+impl Expr {
+    /// Parse a query, reporting where in it any problem is.
+    pub fn parse(query: &str) -> Result<Self> {
+        let tokens = lex(query)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+            len: query.len(),
+        };
+        let expr = parser.or()?;
+        if let Some((token, at)) = parser.tokens.get(parser.pos) {
+            bail!("Invalid query, unexpected {} at column {}", token, at + 1);
+        }
+
+        Ok(expr)
+    }
+
+    /// Whether the note's fields match the query.
+    pub fn matches(&self, fields: &Fields) -> bool {
+        match self {
+            Expr::Or(a, b) => a.matches(fields) || b.matches(fields),
+            Expr::And(a, b) => a.matches(fields) && b.matches(fields),
+            Expr::Not(expr) => !expr.matches(fields),
+            Expr::Truthy(operand) => operand.value(fields).truthy(),
+            Expr::Compare(a, op, b) => {
+                let (a, b) = (a.value(fields), b.value(fields));
+                let order = a.compare(&b, fields.create);
+                match op {
+                    Op::Eq => order == Some(Ordering::Equal),
+                    Op::Ne => order != Some(Ordering::Equal),
+                    Op::Lt => order == Some(Ordering::Less),
+                    Op::Le => matches!(order, Some(Ordering::Less | Ordering::Equal)),
+                    Op::Gt => order == Some(Ordering::Greater),
+                    Op::Ge => matches!(order, Some(Ordering::Greater | Ordering::Equal)),
+                    Op::In => b.contains(&a, fields.create),
+                    Op::Contains => a.contains(&b, fields.create),
+                }
+            }
+        }
+    }
+}
+
+/// Order two field values for sorting, with missing values last.
+pub fn sort_order(a: &Value, b: &Value, create: &Create) -> Ordering {
+    match (a, b) {
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => Ordering::Greater,
+        (_, Value::Null) => Ordering::Less,
+        _ => a
+            .compare(b, create)
+            .unwrap_or_else(|| a.to_string().cmp(&b.to_string())),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Op(Op),
+    Field(String),
+    Literal(Value),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::And => write!(f, "'and'"),
+            Token::Or => write!(f, "'or'"),
+            Token::Not => write!(f, "'not'"),
+            Token::Op(op) => write!(f, "'{}'", op),
+            Token::Field(field) => write!(f, "field '{}'", field),
+            Token::Literal(value) => write!(f, "value '{}'", value),
+        }
+    }
+}
+
+/// Split a query into tokens, each with its byte offset.
+fn lex(query: &str) -> Result<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+
+    while let Some(&(at, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => {
+                chars.next();
+                Token::Open
+            }
+            ')' => {
+                chars.next();
+                Token::Close
+            }
+            '=' | '!' | '<' | '>' => {
+                chars.next();
+                let equals = chars.next_if(|&(_, c)| c == '=').is_some();
+                Token::Op(match (c, equals) {
+                    ('=', _) => Op::Eq,
+                    ('!', true) => Op::Ne,
+                    ('<', false) => Op::Lt,
+                    ('<', true) => Op::Le,
+                    ('>', false) => Op::Gt,
+                    ('>', true) => Op::Ge,
+                    _ => bail!("Invalid query, expected != at column {}", at + 1),
+                })
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, escaped)) => text.push(escaped),
+                            None => break,
+                        },
+                        Some((_, end)) if end == c => {
+                            tokens.push((Token::Literal(Value::Text(text)), at));
+                            break;
+                        }
+                        Some((_, other)) => text.push(other),
+                        None => bail!("Invalid query, unclosed quote at column {}", at + 1),
+                    }
+                }
+                continue;
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some((_, c)) = chars.next_if(|&(_, c)| {
+                    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':' | '+')
+                }) {
+                    word.push(c);
+                }
+                if word.is_empty() {
+                    bail!("Invalid query, unexpected '{}' at column {}", c, at + 1);
+                }
+                word_token(word)
+            }
+        };
+        tokens.push((token, at));
+    }
+
+    Ok(tokens)
+}
+
+/// A bare word, which is a keyword, a number, a date, or otherwise a field name.
+fn word_token(word: String) -> Token {
+    match word.to_lowercase().as_str() {
+        "and" => return Token::And,
+        "or" => return Token::Or,
+        "not" => return Token::Not,
+        "in" => return Token::Op(Op::In),
+        "contains" => return Token::Op(Op::Contains),
+        "true" => return Token::Literal(Value::Bool(true)),
+        "false" => return Token::Literal(Value::Bool(false)),
+        "null" => return Token::Literal(Value::Null),
+        _ => {}
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(&word, "%Y-%m-%d") {
+        return Token::Literal(Value::Date(date));
+    }
+    match word.parse::<f64>() {
+        Ok(n) => Token::Literal(Value::Number(n)),
+        Err(_) => Token::Field(word),
+    }
+}
+
+/// A recursive descent parser over the tokens, where `and` binds tighter than `or`.
+struct Parser<'a> {
+    tokens: &'a [(Token, usize)],
+    pos: usize,
+
+    /// The length of the query, for reporting problems at the end
+    len: usize,
+}
+
+impl Parser<'_> {
+    fn next_if(&mut self, token: &Token) -> bool {
+        match self.tokens.get(self.pos) {
+            Some((next, _)) if next == token => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.next_if(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.not()?;
+        while self.next_if(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        match self.next_if(&Token::Not) {
+            true => Ok(Expr::Not(Box::new(self.not()?))),
+            false => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        if self.next_if(&Token::Open) {
+            let expr = self.or()?;
+            if !self.next_if(&Token::Close) {
+                bail!("Invalid query, expected ')' at column {}", self.column());
+            }
+            return Ok(expr);
+        }
+
+        let left = self.operand()?;
+        match self.tokens.get(self.pos) {
+            Some((Token::Op(op), _)) => {
+                self.pos += 1;
+                Ok(Expr::Compare(left, *op, self.operand()?))
+            }
+            _ => Ok(Expr::Truthy(left)),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand> {
+        let operand = match self.tokens.get(self.pos) {
+            Some((Token::Field(field), _)) => Operand::Field(field.clone()),
+            Some((Token::Literal(value), _)) => Operand::Literal(value.clone()),
+            Some((token, at)) => bail!(
+                "Invalid query, expected a field or value at column {} but found {}",
+                at + 1,
+                token
+            ),
+            None => bail!("Invalid query, expected a field or value at the end"),
+        };
+        self.pos += 1;
+        Ok(operand)
+    }
+
+    /// The column of the next token, or just past the end of the query.
+    fn column(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.len, |(_, at)| *at) + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Timezone;
+
+    /// Settings for notes dated in Tokyo with timestamps in the given format.
+    fn create(timestamp_format: &str) -> Create {
+        Create {
+            frontmatter: Default::default(),
+            timezone: Timezone::Named(chrono_tz::Asia::Tokyo),
+            timestamp_format: timestamp_format.to_string(),
+        }
+    }
+
+    fn matches_as(query: &str, md: &str, create: &Create) -> bool {
+        let fields = Fields::new(
+            md,
+            Path::new("/notes/work/plan.md"),
+            Path::new("/notes"),
+            create,
+        );
+        Expr::parse(query).unwrap().matches(&fields)
+    }
+
+    fn matches(query: &str, md: &str) -> bool {
+        matches_as(query, md, &create("%FT%T%:z"))
+    }
+
+    fn error(query: &str) -> String {
+        Expr::parse(query).unwrap_err().to_string()
+    }
+
+    const NOTE: &str = "---\nstatus: draft\nrating: 4\ndone: false\ntags: [rust, cli]\nauthor:\n  name: Ann\ncreated: 2026-02-01\n---\n# Plan\n\nSee #idea\n";
+
+    #[test]
+    fn compares_fields_with_values() {
+        assert!(matches("status = 'draft'", NOTE));
+        assert!(matches("status != \"final\"", NOTE));
+        assert!(matches("rating >= 4 and rating < 4.5", NOTE));
+        assert!(matches("rating > '3'", NOTE));
+        assert!(matches(
+            "created > 2026-01-31 and created <= 2026-02-01",
+            NOTE
+        ));
+        assert!(matches("author.name = 'Ann'", NOTE));
+        assert!(!matches("missing = null", NOTE));
+        assert!(matches("missing != 1", NOTE));
+    }
+
+    #[test]
+    fn dates_timestamps_in_the_timezone() {
+        // Late on the 1st in UTC is already the 2nd in Tokyo
+        let md = "---\ntimestamp: 2026-02-01T20:00:00+00:00\n---\n";
+        assert!(matches("timestamp = 2026-02-02", md));
+        assert!(matches("timestamp > 2026-02-01", md));
+        assert!(!matches("timestamp < 2026-02-02", md));
+    }
+
+    #[test]
+    fn reads_timestamps_in_the_configured_format() {
+        // Without an offset, the time is in the timezone notes are dated in
+        let md = "---\ntimestamp: 01/02/2026 23:30\n---\n";
+        let create = create("%d/%m/%Y %H:%M");
+        assert!(matches_as("timestamp = 2026-02-01", md, &create));
+        assert!(!matches("timestamp = 2026-02-01", md));
+
+        // RFC 3339 timestamps are still understood
+        let md = "---\ntimestamp: 2026-02-01T20:00:00+00:00\n---\n";
+        assert!(matches_as("timestamp = 2026-02-02", md, &create));
+    }
+
+    #[test]
+    fn checks_lists_and_text() {
+        assert!(matches("'rust' in tags", NOTE));
+        assert!(matches("tags contains 'idea'", NOTE));
+        assert!(matches("status contains 'raf'", NOTE));
+        assert!(!matches("'go' in tags", NOTE));
+        assert!(matches("'raf' in status", NOTE));
+    }
+
+    #[test]
+    fn uses_the_note_for_builtin_fields() {
+        assert!(matches("path = 'work/plan.md' and id = 'plan'", NOTE));
+        assert!(matches("title = 'Plan'", NOTE));
+    }
+
+    #[test]
+    fn combines_with_precedence() {
+        // and binds tighter than or, and not tighter than both
+        assert!(matches(
+            "status = 'final' and rating = 1 or rating = 4",
+            NOTE
+        ));
+        assert!(!matches(
+            "status = 'final' and (rating = 1 or rating = 4)",
+            NOTE
+        ));
+        assert!(matches("not status = 'final' and rating = 4", NOTE));
+        assert!(!matches("not (status = 'draft' or rating = 1)", NOTE));
+        assert!(matches("NOT done AND status", NOTE));
+    }
+
+    #[test]
+    fn fields_alone_are_truthy() {
+        assert!(matches("status", NOTE));
+        assert!(!matches("done", NOTE));
+        assert!(!matches("missing", NOTE));
+        assert!(matches("not missing", NOTE));
+    }
+
+    #[test]
+    fn parses_escaped_quotes() {
+        assert!(matches(r#"title = "it\"s""#, "# it\"s\n"));
+        assert!(matches(r"title = 'it\'s'", "# it's\n"));
+    }
+
+    #[test]
+    fn reports_where_queries_are_invalid() {
+        assert_eq!(
+            error("(status = 'draft'"),
+            "Invalid query, expected ')' at column 18"
+        );
+        assert_eq!(
+            error("status = 'draft"),
+            "Invalid query, unclosed quote at column 10"
+        );
+        assert_eq!(
+            error("status ! 'a'"),
+            "Invalid query, expected != at column 8"
+        );
+        assert_eq!(
+            error("status = = 'a'"),
+            "Invalid query, expected a field or value at column 10 but found '='"
+        );
+        assert_eq!(
+            error("status ="),
+            "Invalid query, expected a field or value at the end"
+        );
+        assert_eq!(
+            error("a b"),
+            "Invalid query, unexpected field 'b' at column 3"
+        );
+        assert_eq!(error("a & b"), "Invalid query, unexpected '&' at column 3");
+    }
+
+    #[test]
+    fn sorts_missing_values_last() {
+        let mut values = vec![Value::Null, Value::Number(2.0), Value::Number(1.0)];
+        values.sort_by(|a, b| sort_order(a, b, &create("%FT%T%:z")));
+        assert_eq!(
+            values,
+            [Value::Number(1.0), Value::Number(2.0), Value::Null]
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! Finding the tasks in notes, which are GitHub-style checklist items such as `- [ ] Call Sam`.
+//!
+//! A task can have a due date, written as `@due(2026-10-20)` or `📅 2026-10-20`, and a priority,
+//! written as `!!!`, `!!`, or `!` for high, medium, or low, or with the `⏫`, `🔼`, and `🔽` markers.
+//! Tasks are referred to by the note's filename without its extension and the line they are on, such
+//! as `20240101_120000:12`.
+
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+
+use crate::{frontmatter, note};
+
+/// How important a task is, with the most important first so that tasks sort by priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    High,
+    Medium,
+    Low,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Priority::High => "!!!",
+            Priority::Medium => "!!",
+            Priority::Low => "!",
+        })
+    }
+}
+
+/// A checklist item in a note.
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub path: PathBuf,
+
+    /// The line the task is on, counting from 1
+    pub line: usize,
+    pub done: bool,
+
+    /// The task's text without its checkbox, due date, or priority
+    pub text: String,
+    pub due: Option<NaiveDate>,
+    pub priority: Option<Priority>,
+
+    /// Tags on the task itself along with those of the note it is in, outside of other tasks
+    pub tags: Vec<String>,
+}
+
+impl Task {
+    /// The ID used to refer to the task, made of the note's file stem and the line number.
+    pub fn id(&self) -> String {
+        let stem = self
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        format!("{}:{}", stem, self.line)
+    }
+}
+
+/// Every task in the note at the given path.
+pub fn read(path: &Path) -> Result<Vec<Task>> {
+    let md = fs::read_to_string(path)
+        .with_context(|| format!("Unable to read note {}", path.to_string_lossy()))?;
+
+    // Tags on other tasks in the note don't apply to this one
+    let front_matter = frontmatter::parse(&md).unwrap_or_default();
+    let tags = frontmatter::strings(&front_matter, "tags")
+        .into_iter()
+        .map(|tag| tag.trim_start_matches('#').to_lowercase())
+        .chain(
+            note::prose(&md)
+                .filter(|(_, line)| checkbox(line).is_none())
+                .flat_map(|(_, line)| note::inline_tags(line)),
+        )
+        .collect::<Vec<_>>();
+
+    Ok(note::prose(&md)
+        .filter_map(|(line, text)| {
+            let (done, _, rest) = checkbox(text)?;
+            let mut task = parse(rest);
+            task.path = path.to_path_buf();
+            task.line = line;
+            task.done = done;
+            task.tags.extend(tags.iter().cloned());
+            task.tags.sort();
+            task.tags.dedup();
+            Some(task)
+        })
+        .collect())
+}
+
+/// Tick the task with the given ID in the notes under the root, returning the ticked task.
+pub fn complete(root: &Path, id: &str) -> Result<Task> {
+    let (stem, line) = id
+        .rsplit_once(':')
+        .and_then(|(stem, line)| Some((stem, line.parse::<usize>().ok()?)))
+        .with_context(|| format!("Invalid task ID '{}', expected e.g. 20240101_120000:12", id))?;
+
+    let notes = note::walk(root)?
+        .into_iter()
+        .filter(|path| {
+            path.file_stem()
+                .is_some_and(|s| s.to_string_lossy() == stem)
+        })
+        .collect::<Vec<_>>();
+    let path = match notes.as_slice() {
+        [] => bail!("No note named {} for task {}", stem, id),
+        [path] => path,
+        _ => bail!(
+            "Several notes are named {}, unable to tell which has task {}",
+            stem,
+            id
+        ),
+    };
+
+    let md = fs::read_to_string(path)
+        .with_context(|| format!("Unable to read note {}", path.to_string_lossy()))?;
+    let task = read(path)?
+        .into_iter()
+        .find(|task| task.line == line)
+        .with_context(|| format!("No task on line {} of {}", line, path.to_string_lossy()))?;
+    if task.done {
+        bail!("Task {} is already done", id);
+    }
+
+    // Replace the space in the box, keeping every other byte of the note as it was
+    let mut ticked = String::with_capacity(md.len());
+    for (i, text) in md.split_inclusive('\n').enumerate() {
+        match (i + 1 == line, checkbox(text)) {
+            (true, Some((_, offset, _))) => {
+                ticked.push_str(&text[..offset]);
+                ticked.push('x');
+                ticked.push_str(&text[offset + 1..]);
+            }
+            _ => ticked.push_str(text),
+        }
+    }
+    fs::write(path, ticked)
+        .with_context(|| format!("Unable to update note {}", path.to_string_lossy()))?;
+
+    Ok(task)
+}
+
+/// Parse the checkbox at the start of a list item, returning whether it is ticked, the byte offset
+/// of the character inside the box, and the text after it.
+fn checkbox(line: &str) -> Option<(bool, usize, &str)> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+
+    // Bullets, or numbered items such as 1. and 1)
+    let marker = match trimmed.chars().next()? {
+        '-' | '*' | '+' => 1,
+        c if c.is_ascii_digit() => {
+            let digits = trimmed.find(|c: char| !c.is_ascii_digit())?;
+            match trimmed[digits..].starts_with(['.', ')']) {
+                true => digits + 1,
+                false => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    let rest = trimmed[marker..].strip_prefix(' ')?.trim_start();
+    let offset = indent + (trimmed.len() - rest.len()) + 1;
+    let done = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let text = &rest[3..];
+    match text.is_empty() || text.starts_with(char::is_whitespace) {
+        true => Some((done, offset, text.trim())),
+        false => None,
+    }
+}
+
+/// Pull the due date, priority, and tags out of a task's text.
+fn parse(text: &str) -> Task {
+    let mut due = None;
+    let mut priority = None;
+    let mut words = Vec::new();
+
+    let mut tokens = text.split_whitespace().peekable();
+    while let Some(token) = tokens.next() {
+        if let Some(date) = token
+            .strip_prefix("@due(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            due = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+            continue;
+        }
+        if token == "📅" {
+            if let Some(date) = tokens
+                .peek()
+                .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            {
+                due = Some(date);
+                tokens.next();
+                continue;
+            }
+        }
+
+        let marker = match token {
+            "!!!" | "⏫" => Some(Priority::High),
+            "!!" | "🔼" => Some(Priority::Medium),
+            "!" | "🔽" => Some(Priority::Low),
+            _ => None,
+        };
+        match marker {
+            Some(marker) => priority = Some(marker),
+            None => words.push(token),
+        }
+    }
+
+    let text = words.join(" ");
+    Task {
+        path: PathBuf::new(),
+        line: 0,
+        done: false,
+        tags: note::inline_tags(&text),
+        text,
+        due,
+        priority,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_checkboxes() {
+        assert_eq!(checkbox("- [ ] Call Sam"), Some((false, 3, "Call Sam")));
+        assert_eq!(checkbox("  * [x] Done  "), Some((true, 5, "Done")));
+        assert_eq!(checkbox("+ [X]"), Some((true, 3, "")));
+        assert_eq!(checkbox("12. [ ] Numbered"), Some((false, 5, "Numbered")));
+        assert_eq!(checkbox("1) [ ] Numbered"), Some((false, 4, "Numbered")));
+        assert_eq!(checkbox("-  [ ] Spaced"), Some((false, 4, "Spaced")));
+    }
+
+    #[test]
+    fn rejects_other_lines() {
+        for line in [
+            "[ ] No bullet",
+            "-[ ] No space",
+            "- [] Empty box",
+            "- [y] Other mark",
+            "- [ ]No space after",
+            "1 [ ] No dot",
+            "- Plain item",
+            "",
+        ] {
+            assert_eq!(checkbox(line), None, "{}", line);
+        }
+    }
+
+    #[test]
+    fn parses_due_dates_and_priorities() {
+        let task = parse("Call Sam @due(2026-10-20) !!! #work");
+        assert_eq!(task.text, "Call Sam #work");
+        assert_eq!(task.due, NaiveDate::from_ymd_opt(2026, 10, 20));
+        assert_eq!(task.priority, Some(Priority::High));
+        assert_eq!(task.tags, ["work"]);
+
+        let task = parse("Book 📅 2026-01-02 🔽 room");
+        assert_eq!(task.text, "Book room");
+        assert_eq!(task.due, NaiveDate::from_ymd_opt(2026, 1, 2));
+        assert_eq!(task.priority, Some(Priority::Low));
+
+        // A calendar without a date is kept as text, and invalid dates are dropped
+        let task = parse("See 📅 soon @due(tomorrow) !!");
+        assert_eq!(task.text, "See 📅 soon");
+        assert_eq!(task.due, None);
+        assert_eq!(task.priority, Some(Priority::Medium));
+    }
+}
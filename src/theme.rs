@@ -0,0 +1,48 @@
+//! Built-in themes, embedded in the binary so that rendered output is presentable without any
+//! stylesheets of its own. User stylesheets are layered on top of the theme.
+
+use std::fmt::{self, Display};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// Print rules shared by every theme.
+const PRINT_CSS: &str = include_str!("themes/print.css");
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    /// A clean sans-serif layout for reading on screen.
+    #[default]
+    Default,
+
+    /// A traditional print-oriented serif layout.
+    Serif,
+
+    /// Light text on a dark background, printing in dark ink.
+    Dark,
+
+    /// No theme, leaving only user stylesheets.
+    None,
+}
+
+impl Theme {
+    /// The css for the theme, including the shared print rules.
+    pub fn css(&self) -> Option<[&'static str; 2]> {
+        let css = match self {
+            Theme::Default => include_str!("themes/default.css"),
+            Theme::Serif => include_str!("themes/serif.css"),
+            Theme::Dark => include_str!("themes/dark.css"),
+            Theme::None => return None,
+        };
+
+        Some([css, PRINT_CSS])
+    }
+}
+
+impl Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.to_possible_value().expect("No themes are skipped");
+        write!(f, "{}", value.get_name())
+    }
+}
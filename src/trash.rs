@@ -0,0 +1,191 @@
+//! The trash, a hidden folder in the notes root that removed notes are moved to.
+//!
+//! Each note in the trash is recorded in a manifest along with where it came from, so that it can
+//! be restored to the same place. Being hidden, the trash is skipped when searching notes.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Timezone, path::create_unique};
+
+const TRASH_DIR: &str = ".trash";
+const MANIFEST_FILE: &str = "manifest.toml";
+
+/// A note in the trash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// The note's filename in the trash, which is also how it is referred to
+    pub id: String,
+
+    /// Where the note was, relative to the notes root
+    pub path: String,
+
+    /// When the note was moved to the trash
+    pub deleted: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    #[serde(default, rename = "entry")]
+    entries: Vec<Entry>,
+}
+
+/// The trash in a notes root.
+pub struct Trash {
+    root: PathBuf,
+    dir: PathBuf,
+    manifest: Manifest,
+
+    /// The timezone deletion times are recorded in
+    timezone: Timezone,
+}
+
+impl Trash {
+    /// Open the trash in the notes root, which is only created once something is put in it.
+    pub fn open(root: &Path, timezone: Timezone) -> Result<Self> {
+        let dir = root.join(TRASH_DIR);
+        let path = dir.join(MANIFEST_FILE);
+        let manifest = match path.is_file() {
+            true => toml::from_str(&fs::read_to_string(&path)?).with_context(|| {
+                format!("Unable to read trash manifest {}", path.to_string_lossy())
+            })?,
+            false => Manifest::default(),
+        };
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            dir,
+            manifest,
+            timezone,
+        })
+    }
+
+    /// The notes in the trash, oldest first.
+    pub fn entries(&self) -> &[Entry] {
+        &self.manifest.entries
+    }
+
+    /// Move the note to the trash.
+    pub fn put(&mut self, note: &Path) -> Result<Entry> {
+        let path = note
+            .strip_prefix(&self.root)
+            .context("Only notes inside the notes root can be moved to the trash")?;
+        let now = self.timezone.now();
+
+        // Prefix the deletion time so notes with the same name can be in the trash together
+        fs::create_dir_all(&self.dir)?;
+        let name = format!(
+            "{}_{}",
+            now.format("%Y%m%d_%H%M%S"),
+            note.file_name()
+                .context("The note has no filename")?
+                .to_string_lossy()
+        );
+        let (_, trashed) = create_unique(&self.dir.join(name))?;
+        if let Err(err) = fs::rename(note, &trashed) {
+            let _ = fs::remove_file(&trashed);
+            return Err(err).with_context(|| {
+                format!("Unable to move {} to the trash", note.to_string_lossy())
+            });
+        }
+
+        let entry = Entry {
+            id: trashed
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            path: path.to_string_lossy().into_owned(),
+            deleted: now.format("%FT%T%:z").to_string(),
+        };
+        self.manifest.entries.push(entry.clone());
+        self.save()?;
+
+        Ok(entry)
+    }
+
+    /// Find the entry for a note by its ID or original path, or by part of either as long as only
+    /// one note matches.
+    pub fn find(&self, query: &str) -> Result<&Entry> {
+        let entries = &self.manifest.entries;
+        if let Some(entry) = entries
+            .iter()
+            .rev()
+            .find(|entry| entry.id == query || entry.path == query)
+        {
+            return Ok(entry);
+        }
+
+        let matches = entries
+            .iter()
+            .filter(|entry| entry.id.contains(query) || entry.path.contains(query))
+            .collect::<Vec<_>>();
+        match matches.as_slice() {
+            [] => bail!("No note in the trash matches '{}'", query),
+            [entry] => Ok(entry),
+            _ => bail!(
+                "{} notes in the trash match '{}', use one of their IDs:\n{}",
+                matches.len(),
+                query,
+                matches
+                    .iter()
+                    .map(|entry| format!("  {}  {}", entry.id, entry.path))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+        }
+    }
+
+    /// Move a note from the trash back to where it was, returning its restored path.
+    pub fn restore(&mut self, id: &str) -> Result<PathBuf> {
+        let index = self
+            .manifest
+            .entries
+            .iter()
+            .position(|entry| entry.id == id)
+            .with_context(|| format!("No note in the trash with ID {}", id))?;
+        let entry = &self.manifest.entries[index];
+
+        let restored = self.root.join(&entry.path);
+        if restored.exists() {
+            bail!(
+                "Unable to restore {}, {} already exists",
+                entry.id,
+                restored.to_string_lossy()
+            );
+        }
+        if let Some(parent) = restored.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(self.dir.join(&entry.id), &restored)
+            .with_context(|| format!("Unable to restore {}", entry.id))?;
+
+        self.manifest.entries.remove(index);
+        self.save()?;
+
+        Ok(restored)
+    }
+
+    /// Permanently delete everything in the trash, returning the number of notes deleted.
+    pub fn empty(&mut self) -> Result<usize> {
+        let count = self.manifest.entries.len();
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir).with_context(|| {
+                format!("Unable to empty the trash {}", self.dir.to_string_lossy())
+            })?;
+        }
+        self.manifest.entries.clear();
+
+        Ok(count)
+    }
+
+    fn save(&self) -> Result<()> {
+        let content = toml::to_string_pretty(&self.manifest)?;
+        fs::write(self.dir.join(MANIFEST_FILE), content)?;
+        Ok(())
+    }
+}
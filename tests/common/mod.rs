@@ -0,0 +1,106 @@
+//! Helpers shared by the integration tests, which drive the compiled binary against a throwaway
+//! notes root.
+
+// Each test crate only uses some of the helpers
+#![allow(dead_code)]
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use tempfile::TempDir;
+
+/// A temporary notes root along with a config file pointing at it.
+pub struct Notes {
+    /// Holds the config, the root, and anything else a test needs outside the root
+    pub dir: TempDir,
+    pub root: PathBuf,
+    pub config: PathBuf,
+}
+
+impl Notes {
+    /// An empty notes root.
+    pub fn new() -> Self {
+        Self::with_config("")
+    }
+
+    /// An empty notes root, with the given settings after the root in the config.
+    pub fn with_config(settings: &str) -> Self {
+        let dir = tempfile::tempdir().expect("Unable to create temp dir");
+        let root = dir.path().join("notes");
+        let config = dir.path().join("conf.toml");
+        fs::write(
+            &config,
+            format!("root = '{}'\n{}", root.to_string_lossy(), settings),
+        )
+        .expect("Unable to write config");
+        fs::create_dir_all(&root).expect("Unable to create notes root");
+
+        Self { dir, root, config }
+    }
+
+    /// Write a file under the root, creating its folders, returning its path.
+    pub fn write(&self, path: &str, content: &str) -> PathBuf {
+        let path = self.root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    /// The content of a file under the root.
+    pub fn read(&self, path: &str) -> String {
+        fs::read_to_string(self.root.join(path)).unwrap()
+    }
+
+    /// A jot command using the config, run from outside the root and without any `JOT_*` env vars
+    /// from the environment the tests run in.
+    pub fn jot(&self) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_jot"));
+        for (key, _) in env::vars_os() {
+            if key.to_string_lossy().starts_with("JOT_") {
+                cmd.env_remove(key);
+            }
+        }
+        cmd.current_dir(self.dir.path())
+            .arg("--config")
+            .arg(&self.config);
+        cmd
+    }
+
+    /// Run jot with the arguments, checking that it succeeds.
+    pub fn run(&self, args: &[&str]) -> Output {
+        let output = self.jot().args(args).output().unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        output
+    }
+
+    /// Run jot with the arguments, checking that it succeeds, returning what it printed.
+    pub fn stdout(&self, args: &[&str]) -> String {
+        String::from_utf8(self.run(args).stdout).unwrap()
+    }
+
+    /// Run jot with the arguments, checking that it fails, returning its error.
+    pub fn stderr(&self, args: &[&str]) -> String {
+        let output = self.jot().args(args).output().unwrap();
+        assert!(!output.status.success(), "{:?}", output);
+        String::from_utf8(output.stderr).unwrap()
+    }
+}
+
+/// Collect every markdown file under the given directory.
+pub fn atoms(dir: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                found.extend(atoms(&path));
+            } else if path.extension().is_some_and(|ext| ext == "md") {
+                found.push(path);
+            }
+        }
+    }
+    found
+}
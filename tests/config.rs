@@ -0,0 +1,163 @@
+//! Tests for how settings are layered from config files, the environment, and arguments, and for editing them with
+//! `jot config`.
+
+mod common;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use common::Notes;
+
+/// Create a note from the given directory, returning the path it was written to.
+fn create(notes: &Notes, dir: &Path, args: &[&str]) -> PathBuf {
+    let output = notes
+        .jot()
+        .current_dir(dir)
+        .args(args)
+        .args(["--no-edit", "A note"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    PathBuf::from(String::from_utf8(output.stdout).unwrap().trim())
+}
+
+#[test]
+fn selects_notebooks() {
+    let home = tempfile::tempdir().unwrap();
+    let notes = Notes::with_config(&format!(
+        "subdir = 'top'\nnotebook = 'work'\n\n[notebooks.work]\nsubdir = 'work'\n\n[notebooks.home]\nroot = '{}'\n",
+        home.path().to_string_lossy()
+    ));
+    let dir = notes.dir.path();
+
+    // The default notebook overrides the top level, which fills in what it leaves out
+    assert!(create(&notes, dir, &[]).starts_with(notes.root.join("work")));
+    assert!(create(&notes, dir, &["--notebook", "home"]).starts_with(home.path().join("top")));
+
+    let stderr = notes.stderr(&["--notebook", "missing", "--no-edit", "A note"]);
+    assert!(
+        stderr.contains("Notebook 'missing' is not defined in"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn discovers_local_config_from_subdirectories() {
+    let notes = Notes::new();
+    let project = notes.dir.path().join("project");
+    let nested = project.join("deep/er");
+    fs::create_dir_all(&nested).unwrap();
+    fs::write(project.join(".jot.toml"), "subdir = 'local'\n").unwrap();
+
+    // A local file without a root makes its own directory the notes root
+    assert!(create(&notes, &nested, &[]).starts_with(project.join("local")));
+    assert!(create(&notes, notes.dir.path(), &[]).starts_with(notes.root.join("atoms")));
+}
+
+#[test]
+fn sets_values_as_valid_toml() {
+    let notes = Notes::with_config("# My settings\nsubdir = 'old' # keep me\n");
+
+    notes.run(&["config", "set", "subdir", "new"]);
+    notes.run(&["config", "set", "jump", "false"]);
+    notes.run(&["config", "set", "notebooks.work.subdir", "work notes"]);
+
+    let content = fs::read_to_string(&notes.config).unwrap();
+    let parsed = content.parse::<toml::Table>().unwrap();
+    assert_eq!(parsed["subdir"].as_str(), Some("new"));
+    assert_eq!(parsed["jump"].as_bool(), Some(false));
+    assert_eq!(
+        parsed["notebooks"]["work"]["subdir"].as_str(),
+        Some("work notes")
+    );
+    assert!(content.contains("\n# My settings\nsubdir = \"new\" # keep me\n"));
+    assert!(!content.contains("[notebooks]\n"));
+    assert_eq!(
+        notes.stdout(&["--notebook", "work", "config", "get", "subdir"]),
+        "work notes\n"
+    );
+
+    // Values that don't fit the schema are refused, leaving the file as it was
+    let stderr = notes.stderr(&["config", "set", "jump", "often"]);
+    assert!(stderr.contains("jump"), "{}", stderr);
+    assert_eq!(fs::read_to_string(&notes.config).unwrap(), content);
+}
+
+#[test]
+fn reports_the_path_of_unknown_keys() {
+    let notes = Notes::with_config(
+        "[notebooks.work]
+subdr = 'work'
+",
+    );
+
+    let stderr = notes.stderr(&["config", "show"]);
+    assert!(
+        stderr.contains(&format!(
+            "Invalid config file {} at key `notebooks.work.subdr`",
+            notes.config.to_string_lossy()
+        )),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("unknown field `subdr`"), "{}", stderr);
+}
+
+#[test]
+fn layers_settings_by_precedence() {
+    let notes = Notes::new();
+    let project = notes.dir.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    let show = |env: Option<&str>, args: &[&str]| {
+        let mut cmd = notes.jot();
+        if let Some(subdir) = env {
+            cmd.env("JOT_SUBDIR", subdir);
+        }
+        let output = cmd
+            .current_dir(&project)
+            .args(args)
+            .args(["config", "show"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let line = stdout.lines().find(|line| line.starts_with("subdir = "));
+        line.unwrap()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    assert_eq!(show(None, &[]), "subdir = \"atoms\" # default");
+
+    fs::write(
+        &notes.config,
+        format!(
+            "{}subdir = 'global'\n",
+            fs::read_to_string(&notes.config).unwrap()
+        ),
+    )
+    .unwrap();
+    assert_eq!(
+        show(None, &[]),
+        format!(
+            "subdir = \"global\" # file: {}",
+            notes.config.to_string_lossy()
+        )
+    );
+
+    let local = project.join(".jot.toml");
+    fs::write(&local, "root = '../notes'\nsubdir = 'local'\n").unwrap();
+    assert_eq!(
+        show(None, &[]),
+        format!("subdir = \"local\" # file: {}", local.to_string_lossy())
+    );
+    assert_eq!(show(Some("env"), &[]), "subdir = \"env\" # env: JOT_SUBDIR");
+    assert_eq!(
+        show(Some("env"), &["--subdir", "cli"]),
+        "subdir = \"cli\" # cli: --subdir"
+    );
+}
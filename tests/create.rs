@@ -0,0 +1,211 @@
+//! Regression tests for creating atoms with the default `jot` command.
+//!
+//! These drive the compiled binary against a throwaway notes root so that concurrent invocations are real separate
+//! processes racing on the filesystem, exactly as they would be when jot is called from scripts.
+
+mod common;
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use common::{atoms, Notes};
+
+/// A jot command using the notes' config and never opening an editor.
+fn jot(notes: &Notes) -> Command {
+    let mut cmd = notes.jot();
+    cmd.arg("--no-edit");
+    cmd
+}
+
+#[test]
+fn creates_atom_and_reports_path() {
+    let notes = Notes::new();
+
+    let output = jot(&notes).arg("A new thought").output().unwrap();
+    assert!(output.status.success());
+
+    let reported = PathBuf::from(String::from_utf8(output.stdout).unwrap().trim());
+    let created = atoms(&notes.root);
+    assert_eq!(created, vec![reported.clone()]);
+
+    let content = fs::read_to_string(reported).unwrap();
+    assert!(content.starts_with("---\ntimestamp: "));
+    assert!(content.ends_with("\n---\n\n# A new thought\n"));
+}
+
+#[test]
+fn writes_title_and_aliases_to_front_matter() {
+    let notes = Notes::new();
+
+    let output = jot(&notes)
+        .args(["# Plan: part 2", "--alias", "second plan", "-a", "yes"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let reported = PathBuf::from(String::from_utf8(output.stdout).unwrap().trim());
+    let content = fs::read_to_string(reported).unwrap();
+    assert!(content.contains(
+        "\ntitle: 'Plan: part 2'\naliases:\n  - second plan\n  - yes\n---\n\n# Plan: part 2\n"
+    ));
+}
+
+#[test]
+fn writes_custom_front_matter_fields() {
+    let notes = Notes::with_config(
+        "[create.frontmatter]\nauthor = '${JOT_TEST_AUTHOR}: me'\nyear = '%Y'\ntags = ['a, b']\n",
+    );
+
+    let output = jot(&notes)
+        .env("JOT_TEST_AUTHOR", "Ann")
+        .args(["Fields", "--meta", "rating=4", "--meta", "title=Other"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let reported = PathBuf::from(String::from_utf8(output.stdout).unwrap().trim());
+    let content = fs::read_to_string(reported).unwrap();
+    let year = content[15..19].to_string();
+    assert!(content.contains(&format!(
+        "\ntitle: Other\nauthor: 'Ann: me'\nyear: '{}'\ntags: [\"a, b\"]\nrating: 4\n---\n",
+        year
+    )));
+}
+
+#[test]
+fn backdates_notes_in_a_timezone() {
+    let notes =
+        Notes::with_config("[create]\ntimezone = 'Asia/Tokyo'\ntimestamp_format = '%F %R %Z'\n");
+
+    let output = jot(&notes)
+        .args(["Meeting", "--date", "2026-10-17T23:30:00Z"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let reported = PathBuf::from(String::from_utf8(output.stdout).unwrap().trim());
+    assert!(reported.ends_with("atoms/2026/10/20261018_083000.md"));
+    assert!(fs::read_to_string(reported)
+        .unwrap()
+        .starts_with("---\ntimestamp: 2026-10-18 08:30 +09:00\n"));
+
+    let output = jot(&notes)
+        .args(["Earlier", "--utc", "--date", "2025-12-31 23:59"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert!(notes.root.join("atoms/2025/12/20251231_235900.md").exists());
+}
+
+#[test]
+fn aborts_without_text_or_force() {
+    let notes = Notes::new();
+
+    let output = jot(&notes).output().unwrap();
+    assert!(output.status.success());
+    assert!(atoms(&notes.root).is_empty());
+}
+
+#[test]
+fn discards_notes_left_empty_or_unchanged() {
+    let notes = Notes::with_config("editor = 'true'\njump = false\ndiscard_empty = true\n");
+
+    // Quitting the editor without writing anything deletes a note started with nothing in it
+    let output = notes.run(&[]);
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Discarded empty note "));
+    assert!(atoms(&notes.root).is_empty());
+
+    // So does leaving a pre-filled note exactly as jot wrote it
+    let output = notes.run(&["Left as it was", "--meta", "status=draft"]);
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Discarded unchanged note "));
+    assert!(atoms(&notes.root).is_empty());
+
+    notes.run(&["--keep", "Left as it was"]);
+    assert_eq!(atoms(&notes.root).len(), 1);
+
+    // Any change at all keeps the note
+    let notes = Notes::with_config(
+        "editor = \"sh -c 'echo >> \\\"$0\\\"'\"\njump = false\ndiscard_empty = true\n",
+    );
+    notes.run(&["Added to"]);
+    assert_eq!(atoms(&notes.root).len(), 1);
+}
+
+#[test]
+fn sequential_invocations_never_overwrite() {
+    let notes = Notes::new();
+
+    for i in 0..5 {
+        let status = jot(&notes).arg(format!("Note {}", i)).status().unwrap();
+        assert!(status.success());
+    }
+
+    let created = atoms(&notes.root);
+    assert_eq!(created.len(), 5);
+    for i in 0..5 {
+        let heading = format!("# Note {}\n", i);
+        assert!(created
+            .iter()
+            .any(|path| fs::read_to_string(path).unwrap().contains(&heading)));
+    }
+}
+
+#[test]
+fn concurrent_invocations_never_lose_data() {
+    const COUNT: usize = 16;
+    let notes = Notes::new();
+
+    // Spawn everything before waiting on anything so the processes race within the same second
+    let children = (0..COUNT)
+        .map(|i| {
+            jot(&notes)
+                .arg(format!("Concurrent note {}", i))
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let reported = children
+        .into_iter()
+        .map(|child| {
+            let output = child.wait_with_output().unwrap();
+            assert!(output.status.success());
+            PathBuf::from(String::from_utf8(output.stdout).unwrap().trim())
+        })
+        .collect::<HashSet<_>>();
+    assert_eq!(reported.len(), COUNT, "Every jot must report its own file");
+
+    let created = atoms(&notes.root);
+    assert_eq!(created.len(), COUNT);
+    assert_eq!(created.into_iter().collect::<HashSet<_>>(), reported);
+
+    for i in 0..COUNT {
+        let heading = format!("# Concurrent note {}\n", i);
+        assert!(
+            reported
+                .iter()
+                .any(|path| fs::read_to_string(path).unwrap().ends_with(&heading)),
+            "Missing content for note {}",
+            i
+        );
+    }
+}
+
+#[test]
+fn exits_with_the_editor_status() {
+    let notes = Notes::with_config("editor = \"sh -c 'exit 3'\"\n");
+
+    let output = notes.jot().arg("Edited").output().unwrap();
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Editor sh exited with status 3"));
+}
@@ -0,0 +1,102 @@
+//! Tests for exporting notes to other formats with `jot export`.
+
+mod common;
+
+use std::{fs, io::Write, process::Stdio};
+
+use common::Notes;
+
+/// Build a notes root with two linked notes and an image.
+fn setup() -> Notes {
+    let notes = Notes::new();
+    notes.write(
+        "first.md",
+        "---\ntitle: First\ntags: [a]\n---\n# First *note*\n\nSee [the second](second.md) and ![chart](img/chart.png).\n\n- [x] done\n  - nested\n",
+    );
+    notes.write("second.md", "# Second\n\nBack to [first](first.md).\n");
+    notes.write("img/chart.png", "png");
+
+    notes
+}
+
+#[test]
+fn exports_plain_text_and_json() {
+    let notes = setup();
+
+    let output = notes.run(&["export", "--format", "txt", "first.md", "second.md"]);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "First note\n\nSee the second and chart.\n\n- [x] done\n  - nested\n\nSecond\n\nBack to first.\n"
+    );
+
+    let output = notes.run(&["export", "-f", "json", "first.md"]);
+    let note: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(note["path"], "first.md");
+    assert_eq!(note["title"], "First");
+    assert_eq!(note["front_matter"]["tags"][0], "a");
+    assert_eq!(note["ast"]["type"], "root");
+    assert_eq!(note["ast"]["children"][0]["type"], "yaml");
+
+    let output = notes.run(&["export", "-f", "json", "-q", "title = \"Second\""]);
+    let notes: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(notes.as_array().unwrap().len(), 1);
+    assert_eq!(notes[0]["id"], "second");
+}
+
+#[test]
+fn exports_epub_books() {
+    let notes = setup();
+    let book = notes.dir.path().join("book.epub");
+
+    let args = [
+        "export",
+        "-f",
+        "epub",
+        "--all",
+        "-o",
+        book.to_str().unwrap(),
+    ];
+    notes.run(&args);
+    let epub = fs::read(&book).unwrap();
+    assert!(epub.starts_with(b"PK"));
+    assert_eq!(&epub[30..38], b"mimetype");
+    assert_eq!(&epub[38..58], b"application/epub+zip");
+
+    // Refuses to overwrite the book
+    assert!(notes.stderr(&args).contains("already exists"));
+}
+
+#[test]
+fn exports_paths_listed_on_stdin_in_order() {
+    let notes = setup();
+    let paths = notes.stdout(&[
+        "query",
+        "title",
+        "--sort",
+        "title",
+        "--reverse",
+        "--format",
+        "paths",
+    ]);
+
+    let mut child = notes
+        .jot()
+        .args(["export", "-f", "json", "--paths"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(paths.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let exported: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(exported[0]["id"], "second");
+    assert_eq!(exported[1]["id"], "first");
+    assert_eq!(exported.as_array().unwrap().len(), 2);
+}
@@ -0,0 +1,190 @@
+//! Tests for committing and syncing notes with git.
+//!
+//! Each test works on clones of a local bare repository standing in for the remote, with git's
+//! global config isolated so that the user's own settings can't affect the results.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use tempfile::TempDir;
+
+/// A scratch area holding a bare remote that clones are made from.
+struct Remote {
+    dir: TempDir,
+    bare: PathBuf,
+}
+
+impl Remote {
+    fn new() -> Self {
+        let dir = tempfile::tempdir().expect("Unable to create temp dir");
+        let bare = dir.path().join("remote.git");
+        git(
+            dir.path(),
+            &["init", "--quiet", "--bare", "--initial-branch=main"],
+        )
+        .arg(&bare)
+        .status()
+        .unwrap();
+
+        Self { dir, bare }
+    }
+
+    /// Clone the remote as a notes root, with a config file using it, returning the config path.
+    fn clone(&self, name: &str, git_settings: &str) -> (PathBuf, PathBuf) {
+        let root = self.dir.path().join(name);
+        git(self.dir.path(), &["clone", "--quiet"])
+            .arg(&self.bare)
+            .arg(&root)
+            .status()
+            .unwrap();
+        git(&root, &["checkout", "--quiet", "-B", "main"])
+            .status()
+            .unwrap();
+
+        let config = self.dir.path().join(format!("{}.toml", name));
+        fs::write(
+            &config,
+            format!(
+                "root = '{}'\n\n[git]\n{}\n",
+                root.to_string_lossy(),
+                git_settings
+            ),
+        )
+        .expect("Unable to write config");
+
+        (root, config)
+    }
+}
+
+/// A git command in the given directory, isolated from the user's git config.
+fn git(dir: &Path, args: &[&str]) -> Command {
+    let mut cmd = Command::new("git");
+    isolate(&mut cmd);
+    cmd.arg("-C").arg(dir).args(args);
+    cmd
+}
+
+/// A jot command using the given config.
+fn jot(config: &Path, args: &[&str]) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_jot"));
+    isolate(&mut cmd);
+    cmd.arg("--config").arg(config).args(args);
+    cmd
+}
+
+fn isolate(cmd: &mut Command) {
+    cmd.env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .env("GIT_AUTHOR_NAME", "Jot")
+        .env("GIT_AUTHOR_EMAIL", "jot@example.com")
+        .env("GIT_COMMITTER_NAME", "Jot")
+        .env("GIT_COMMITTER_EMAIL", "jot@example.com");
+}
+
+fn stdout(output: Output) -> String {
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+/// The subject of the latest commit.
+fn subject(root: &Path) -> String {
+    stdout(git(root, &["log", "-1", "--format=%s"]).output().unwrap())
+}
+
+/// Whether the working tree has no changes.
+fn is_clean(root: &Path) -> bool {
+    stdout(git(root, &["status", "--porcelain"]).output().unwrap()).is_empty()
+}
+
+#[test]
+fn auto_commit_uses_note_heading() {
+    let remote = Remote::new();
+    let (root, config) = remote.clone("notes", "auto_commit = true");
+
+    let output = jot(&config, &["--no-edit", "A committed thought"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    assert_eq!(subject(&root), "A committed thought");
+    assert!(is_clean(&root));
+}
+
+#[test]
+fn commit_all_changes_and_nothing_to_commit() {
+    let remote = Remote::new();
+    let (root, config) = remote.clone("notes", "");
+
+    jot(&config, &["--no-edit", "First"]).status().unwrap();
+    jot(&config, &["--no-edit", "Second"]).status().unwrap();
+    assert!(!is_clean(&root));
+
+    let output = jot(&config, &["commit"]).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(subject(&root), "Update 2 notes");
+    assert!(is_clean(&root));
+
+    let output = jot(&config, &["commit"]).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(stdout(output), "Nothing to commit");
+}
+
+#[test]
+fn sync_pushes_and_pulls_notes() {
+    let remote = Remote::new();
+    let (_, first) = remote.clone("first", "auto_commit = true");
+    let (second_root, second) = remote.clone("second", "");
+
+    let path = stdout(jot(&first, &["--no-edit", "Shared"]).output().unwrap());
+    let output = jot(&first, &["sync"]).output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = jot(&second, &["sync"]).output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert!(stdout(output).contains("Pulled from origin/main"));
+
+    let name = Path::new(&path).file_name().unwrap();
+    let pulled = fs::read_dir(second_root.join("atoms"))
+        .unwrap()
+        .flatten()
+        .flat_map(|year| fs::read_dir(year.path()).unwrap().flatten())
+        .any(|month| month.path().join(name).is_file());
+    assert!(pulled);
+}
+
+#[test]
+fn sync_reports_conflicts_and_keeps_local_commits() {
+    let remote = Remote::new();
+    let (first_root, first) = remote.clone("first", "");
+    let (second_root, second) = remote.clone("second", "");
+
+    // Both clones start from the same note, then change the same line in different ways
+    fs::write(first_root.join("note.md"), "# Original\n").unwrap();
+    assert!(jot(&first, &["sync"]).status().unwrap().success());
+    assert!(jot(&second, &["sync"]).status().unwrap().success());
+
+    fs::write(first_root.join("note.md"), "# From first\n").unwrap();
+    assert!(jot(&first, &["sync"]).status().unwrap().success());
+
+    let note = second_root.join("note.md");
+    fs::write(&note, "# From second\n").unwrap();
+    let output = jot(&second, &["sync"]).output().unwrap();
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("conflict"), "{}", stderr);
+    assert!(stderr.contains("note.md"), "{}", stderr);
+
+    // The pull is undone, leaving the local commit checked out on a clean tree
+    assert_eq!(fs::read_to_string(&note).unwrap(), "# From second\n");
+    assert_eq!(subject(&second_root), "From second");
+    assert!(is_clean(&second_root));
+    let rebase = stdout(
+        git(&second_root, &["rev-parse", "--git-path", "rebase-merge"])
+            .output()
+            .unwrap(),
+    );
+    assert!(!second_root.join(rebase).exists());
+}
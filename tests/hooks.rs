@@ -0,0 +1,78 @@
+//! Tests for the hook commands run around creating and rendering notes.
+
+mod common;
+
+use std::fs;
+
+use common::Notes;
+
+#[test]
+fn renders_changes_made_by_the_pre_render_hook() {
+    let notes = Notes::with_config(
+        "[hooks]\npre_render = 'sed \"s/Draft/Final/\" \"$1\" > \"$1.tmp\" && mv \"$1.tmp\" \"$1\"'\n",
+    );
+    let note = notes.write("note.md", "# Draft report\n");
+
+    let html = notes.stdout(&["html", "-i", note.to_str().unwrap()]);
+    assert!(html.contains("Final report"), "{}", html);
+    assert!(!html.contains("Draft"), "{}", html);
+    assert_eq!(notes.read("note.md"), "# Final report\n");
+}
+
+#[test]
+fn failing_pre_render_hook_stops_rendering_when_fatal() {
+    let notes = Notes::with_config("[hooks]\npre_render = 'exit 3'\nfatal = true\n");
+    let note = notes.write("note.md", "# Report\n");
+    let output = notes.dir.path().join("note.html");
+
+    let stderr = notes.stderr(&[
+        "html",
+        "-i",
+        note.to_str().unwrap(),
+        "-o",
+        output.to_str().unwrap(),
+    ]);
+    assert!(
+        stderr.contains("The pre_render hook exited with status 3"),
+        "{}",
+        stderr
+    );
+    assert!(!output.exists());
+}
+
+#[test]
+fn failing_hooks_only_warn_by_default() {
+    let notes = Notes::with_config("[hooks]\npre_render = 'exit 3'\n");
+    let note = notes.write("note.md", "# Report\n");
+
+    let output = notes.run(&["html", "-i", note.to_str().unwrap()]);
+    assert!(String::from_utf8(output.stdout).unwrap().contains("Report"));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Warning: The pre_render hook exited with status 3"));
+}
+
+#[test]
+fn passes_the_note_to_hooks_in_arguments_and_the_environment() {
+    let notes = Notes::with_config(
+        "[hooks]\npost_create = 'echo \"$JOT_HOOK $1\" > \"$JOT_ROOT/../created\"'\npost_render = 'echo \"$1|$2|$JOT_HOOK|$JOT_NOTE|$JOT_OUTPUT|$JOT_ROOT|$JOT_NOTEBOOK|$JOT_META_TITLE\" > \"$JOT_ROOT/../rendered\"'\n",
+    );
+    let note = notes.write("note.md", "---\ntitle: Report\n---\n# Report\n");
+    let note = fs::canonicalize(note).unwrap();
+    let note = note.to_str().unwrap();
+    let output = notes.dir.path().join("note.html");
+    let output = output.to_str().unwrap();
+
+    notes.run(&["html", "-i", note, "-o", output]);
+    let root = notes.root.to_string_lossy();
+    assert_eq!(
+        fs::read_to_string(notes.dir.path().join("rendered")).unwrap(),
+        format!("{note}|{output}|post_render|{note}|{output}|{root}||Report\n")
+    );
+
+    let created = notes.stdout(&["--no-edit", "New note"]);
+    assert_eq!(
+        fs::read_to_string(notes.dir.path().join("created")).unwrap(),
+        format!("post_create {}", created)
+    );
+}
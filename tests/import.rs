@@ -0,0 +1,100 @@
+//! Tests for importing notes from other note apps with `jot import`.
+
+mod common;
+
+use std::fs;
+
+use common::Notes;
+
+#[test]
+fn imports_an_obsidian_vault_with_links_and_attachments() {
+    let notes = Notes::new();
+    let atoms = notes.root.join("atoms");
+    let vault = notes.dir.path().join("vault");
+    fs::create_dir_all(vault.join("files")).unwrap();
+    fs::create_dir_all(vault.join(".obsidian")).unwrap();
+    fs::write(vault.join(".obsidian/app.json"), "{}").unwrap();
+    fs::write(
+        vault.join("Home.md"),
+        "---\ncreated: 2024-01-02 10:30\n---\nSee [[Later]] #project\n\n![[chart.png|300]] and [gone](missing.md)\n",
+    )
+    .unwrap();
+    fs::write(
+        vault.join("Later.md"),
+        "---\ndate: 2024-03-04T05:06:07Z\n---\nBack [[Home|home]]\n",
+    )
+    .unwrap();
+    fs::write(vault.join("files/chart.png"), "png").unwrap();
+
+    let output = notes.run(&["--utc", "import", "obsidian", vault.to_str().unwrap()]);
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .starts_with("Imported 2 notes and 1 attachment"));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("  Home.md: Link to missing.md not found\n"));
+
+    let home = fs::read_to_string(atoms.join("2024/01/20240102_103000.md")).unwrap();
+    assert_eq!(
+        home,
+        "---\ntimestamp: 2024-01-02T10:30:00+00:00\ntitle: Home\ncreated: 2024-01-02 10:30\nimported-from: obsidian\n---\nSee [[20240304_050607|Later]] #project\n\n![chart.png](<../../attachments/chart.png>) and [gone](missing.md)\n"
+    );
+    let later = fs::read_to_string(atoms.join("2024/03/20240304_050607.md")).unwrap();
+    assert!(later.ends_with("---\nBack [[20240102_103000|home]]\n"));
+    assert_eq!(
+        fs::read_to_string(atoms.join("attachments/chart.png")).unwrap(),
+        "png"
+    );
+}
+
+#[test]
+fn converts_evernote_exports() {
+    let notes = Notes::new();
+    let atoms = notes.root.join("atoms");
+    let enex = notes.dir.path().join("export.enex");
+    fs::write(
+        &enex,
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE en-export SYSTEM "http://xml.evernote.com/pub/evernote-export4.dtd">
+<en-export>
+<note>
+<title>Shopping</title>
+<created>20230405T120000Z</created>
+<tag>home</tag>
+<content><![CDATA[<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE en-note SYSTEM "http://xml.evernote.com/pub/enml2.dtd">
+<en-note><h2>List</h2><div><en-todo checked="true"/>Milk</div><div><en-todo/>Eggs&nbsp;too</div><en-media hash="5d41402abc4b2a76b9719d911017c592" type="text/plain"/></en-note>]]></content>
+<resource><data encoding="base64">aGVsbG8=</data><mime>text/plain</mime></resource>
+</note>
+</en-export>
+"#,
+    )
+    .unwrap();
+
+    notes.run(&["--utc", "import", "enex", enex.to_str().unwrap()]);
+    assert_eq!(
+        fs::read_to_string(atoms.join("2023/04/20230405_120000.md")).unwrap(),
+        "---\ntimestamp: 2023-04-05T12:00:00+00:00\ntitle: Shopping\ntags: [\"home\"]\nimported-from: enex\n---\n\n## List\n\n- [x] Milk\n- [ ] Eggs too\n[attachment-1.txt](<../../attachments/attachment-1.txt>)\n"
+    );
+    assert_eq!(
+        fs::read_to_string(atoms.join("attachments/attachment-1.txt")).unwrap(),
+        "hello"
+    );
+}
+
+#[test]
+fn leaves_no_notes_behind_when_a_timestamp_cannot_be_written() {
+    let notes = Notes::with_config("[create]\ntimestamp_format = '%Q'\n");
+    let dir = notes.dir.path().join("plain");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("one.txt"), "One").unwrap();
+    fs::write(dir.join("two.md"), "Two").unwrap();
+
+    let stderr = notes.stderr(&["import", "plain", dir.to_str().unwrap()]);
+    assert!(
+        stderr.contains("Invalid timestamp format '%Q'"),
+        "{}",
+        stderr
+    );
+    assert!(common::atoms(&notes.root).is_empty());
+}
@@ -0,0 +1,177 @@
+//! Tests for editing front matter in bulk with `jot meta` and `jot migrate`.
+
+mod common;
+
+use std::fs;
+
+use common::Notes;
+
+/// Build a notes root with a couple of notes with inconsistent front matter.
+fn setup() -> Notes {
+    let notes = Notes::new();
+    notes.write(
+        "old.md",
+        "---\ntimestamp: 2023-01-02 10:30:00\ngenerated-by: jot 0.1\ntags:\n  - rust\n---\n# Old note\n\nBody\n",
+    );
+    notes.write(
+        "new.md",
+        "---\ntitle: New note\ntags: [go]\n---\n# New note\n",
+    );
+
+    notes
+}
+
+#[test]
+fn sets_unsets_and_renames_keys() {
+    let notes = setup();
+
+    let diff = notes.stdout(&[
+        "meta",
+        "set",
+        "status",
+        "draft",
+        "-q",
+        "'rust' in tags",
+        "--dry-run",
+    ]);
+    assert!(diff.contains("+status: draft"), "{}", diff);
+    assert!(diff.ends_with("1 note would change\n"), "{}", diff);
+    assert!(!notes.read("old.md").contains("status"));
+
+    notes.stdout(&["meta", "set", "title", "{title}", "--missing", "--all"]);
+    notes.stdout(&["meta", "unset", "generated-by", "old.md"]);
+    notes.stdout(&[
+        "meta",
+        "rename-key",
+        "tags",
+        "keywords",
+        "--all",
+        "--no-backup",
+    ]);
+    assert_eq!(
+        notes.read("old.md"),
+        "---\ntimestamp: 2023-01-02 10:30:00\nkeywords:\n  - rust\ntitle: Old note\n---\n# Old note\n\nBody\n"
+    );
+    assert_eq!(
+        notes.read("new.md"),
+        "---\ntitle: New note\nkeywords: [go]\n---\n# New note\n"
+    );
+
+    // The original of each changed note is kept in a backup folder for the run
+    let backups = fs::read_dir(notes.root.join(".backup")).unwrap().count();
+    assert!(backups >= 1);
+}
+
+#[test]
+fn never_reuses_a_backup_folder() {
+    let notes = setup();
+    // Take the folders for the next few seconds, as if other runs had just started
+    let now = chrono::Local::now();
+    let taken = (0..3)
+        .map(|secs| {
+            let time = now + chrono::Duration::seconds(secs);
+            let name = time.format("%Y%m%d_%H%M%S").to_string();
+            notes.write(&format!(".backup/{}/old.md", name), "Backed up earlier");
+            name
+        })
+        .collect::<Vec<_>>();
+
+    let output = notes.stdout(&["meta", "set", "status", "draft", "old.md"]);
+    let backup = output
+        .lines()
+        .find_map(|line| line.strip_prefix("Backed up 1 note to .backup/"))
+        .unwrap();
+    assert!(
+        taken.iter().any(|name| backup == format!("{}_1", name)),
+        "{}",
+        output
+    );
+    assert!(notes
+        .read(&format!(".backup/{}/old.md", backup))
+        .contains("# Old note"));
+    for name in taken {
+        assert_eq!(
+            notes.read(&format!(".backup/{}/old.md", name)),
+            "Backed up earlier"
+        );
+    }
+}
+
+#[test]
+fn applies_migration_scripts() {
+    let notes = setup();
+    let script = notes.dir.path().join("migrate.toml");
+    fs::write(
+        &script,
+        r#"
+[[step]]
+unset = "generated-by"
+
+[[step]]
+date = "timestamp"
+format = "%Y-%m-%d"
+
+[[step]]
+set = "status"
+value = "archived"
+where = "timestamp < 2024-01-01"
+"#,
+    )
+    .unwrap();
+
+    let output = notes.stdout(&["migrate", script.to_str().unwrap(), "--all", "--no-backup"]);
+    assert_eq!(output, "Updated old.md\nUpdated 1 note\n");
+    assert_eq!(
+        notes.read("old.md"),
+        "---\ntimestamp: 2023-01-02\ntags:\n  - rust\nstatus: archived\n---\n# Old note\n\nBody\n"
+    );
+}
+
+#[test]
+fn reformats_dates_keeping_their_offset() {
+    let notes = setup();
+    notes.write(
+        "tokyo.md",
+        "---\ntimestamp: 2023-05-06T23:30:00+09:00\n---\n",
+    );
+    let script = notes.dir.path().join("migrate.toml");
+    fs::write(
+        &script,
+        "[[step]]\ndate = \"timestamp\"\nformat = \"%F %R %:z\"\n",
+    )
+    .unwrap();
+
+    notes.run(&[
+        "--utc",
+        "migrate",
+        script.to_str().unwrap(),
+        "tokyo.md",
+        "old.md",
+        "--no-backup",
+    ]);
+    assert_eq!(
+        notes.read("tokyo.md"),
+        "---\ntimestamp: 2023-05-06 23:30 +09:00\n---\n"
+    );
+    assert!(notes
+        .read("old.md")
+        .starts_with("---\ntimestamp: 2023-01-02 10:30 +00:00\n"));
+}
+
+#[test]
+fn reports_invalid_date_formats() {
+    let notes = setup();
+    let script = notes.dir.path().join("migrate.toml");
+    fs::write(
+        &script,
+        "[[step]]\ndate = \"timestamp\"\nformat = \"%Y %Q\"\n",
+    )
+    .unwrap();
+
+    let stderr = notes.stderr(&["migrate", script.to_str().unwrap(), "--all"]);
+    assert!(stderr.contains("Invalid date format '%Y %Q'"), "{}", stderr);
+    assert!(!stderr.contains("panicked"), "{}", stderr);
+    assert!(notes
+        .read("old.md")
+        .contains("timestamp: 2023-01-02 10:30:00\n"));
+}
@@ -0,0 +1,76 @@
+//! Tests for moving notes with `jot mv`, which must keep links to the moved note working.
+
+mod common;
+
+use common::Notes;
+
+const TARGET: &str = "atoms/2024/01/20240101_120000.md";
+const LINKING: &str = "projects/plan.md";
+
+/// Build a notes root with one note linking to another in several ways.
+fn setup() -> Notes {
+    let notes = Notes::new();
+    notes.write(TARGET, "# Target\n\n![diagram](../../../diagram.png)\n");
+    notes.write(
+        LINKING,
+        "# Plan\n\n[[20240101_120000|the target]] and [target](../atoms/2024/01/20240101_120000.md#top)\n\n`[[20240101_120000]]` is how to link it\n",
+    );
+
+    notes
+}
+
+#[test]
+fn rewrites_links_to_the_moved_note() {
+    let notes = setup();
+
+    notes.run(&["mv", TARGET, "projects/target"]);
+
+    assert!(!notes.root.join(TARGET).exists());
+    assert_eq!(
+        notes.read("projects/target.md"),
+        "# Target\n\n![diagram](../diagram.png)\n"
+    );
+    assert_eq!(
+        notes.read(LINKING),
+        "# Plan\n\n[[target|the target]] and [target](target.md#top)\n\n`[[20240101_120000]]` is how to link it\n"
+    );
+}
+
+#[test]
+fn keeps_links_by_title_or_alias() {
+    let notes = setup();
+    notes.write(
+        TARGET,
+        "---\ntitle: Target\naliases: [goal]\n---\n\n# Target\n\n[[goal|this note]]\n",
+    );
+    notes.write(LINKING, "# Plan\n\n[[Target]] and [[goal]]\n");
+
+    notes.run(&["mv", TARGET, "projects/done"]);
+
+    assert_eq!(notes.read(LINKING), "# Plan\n\n[[Target]] and [[goal]]\n");
+    assert!(notes
+        .read("projects/done.md")
+        .ends_with("[[goal|this note]]\n"));
+}
+
+#[test]
+fn dry_run_shows_diff_without_changes() {
+    let notes = setup();
+    let before = notes.read(LINKING);
+
+    let stdout = notes.stdout(&["mv", "--dry-run", TARGET, "projects/target"]);
+    assert!(stdout.contains("+++ projects/plan.md"));
+    assert!(stdout.contains("+[[target|the target]] and [target](target.md#top)"));
+
+    assert!(notes.root.join(TARGET).exists());
+    assert!(!notes.root.join("projects/target.md").exists());
+    assert_eq!(notes.read(LINKING), before);
+}
+
+#[test]
+fn refuses_to_overwrite() {
+    let notes = setup();
+
+    notes.stderr(&["mv", TARGET, LINKING]);
+    assert!(notes.root.join(TARGET).exists());
+}
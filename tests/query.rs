@@ -0,0 +1,109 @@
+//! Tests for finding notes by their front matter with `jot query`.
+
+mod common;
+
+use common::Notes;
+
+/// Build a notes root with a few notes with different front matter.
+fn setup() -> Notes {
+    let notes = Notes::new();
+    notes.write(
+        "rust.md",
+        "---\ntitle: Rust draft\nstatus: draft\ntimestamp: 2026-02-01T10:00:00+00:00\ntags: [rust]\nrating: 4\n---\n",
+    );
+    notes.write(
+        "old.md",
+        "---\ntitle: Old draft\nstatus: draft\ntimestamp: 2025-06-01T10:00:00+00:00\ntags: [rust]\nrating: 5\n---\n",
+    );
+    notes.write(
+        "go.md",
+        "---\ntitle: Go, final\nstatus: final\ntimestamp: 2026-03-01T10:00:00+00:00\nrating: 2\n---\n#golang\n",
+    );
+
+    notes
+}
+
+#[test]
+fn filters_sorts_and_selects_fields() {
+    let notes = setup();
+
+    assert_eq!(
+        notes.stdout(&[
+            "query",
+            "status = \"draft\" and timestamp > 2026-01-01 and \"rust\" in tags",
+            "--fields",
+            "title,timestamp",
+        ]),
+        "title       timestamp\nRust draft  2026-02-01T10:00:00+00:00\n"
+    );
+    assert_eq!(
+        notes.stdout(&[
+            "query",
+            "rating > 3 or tags contains 'golang'",
+            "--sort",
+            "rating",
+            "-o",
+            "csv",
+            "--fields",
+            "title,rating"
+        ]),
+        "title,rating\n\"Go, final\",2\nRust draft,4\nOld draft,5\n"
+    );
+}
+
+#[test]
+fn prints_json() {
+    let notes = setup();
+
+    let json = notes.stdout(&[
+        "query",
+        "not status = 'draft'",
+        "-o",
+        "json",
+        "--fields",
+        "id,rating,missing",
+    ]);
+    let notes: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        notes,
+        serde_json::json!([{ "id": "go", "rating": 2, "missing": null }])
+    );
+}
+
+#[test]
+fn reports_invalid_queries() {
+    let notes = setup();
+
+    let stderr = notes.stderr(&["query", "(status = 'draft'"]);
+    assert!(stderr.contains("expected ')' at column 18"), "{}", stderr);
+}
+
+#[test]
+fn reads_timestamps_in_the_configured_format() {
+    let notes = Notes::with_config(
+        "[create]\ntimezone = 'Asia/Tokyo'\ntimestamp_format = '%d/%m/%Y %H:%M'\n",
+    );
+    notes.run(&["-e", "--date", "2026-02-01 23:30", "Late night"]);
+    notes.run(&["-e", "--date", "2026-02-02 08:00", "Next morning"]);
+
+    let atom = notes.stdout(&["query", "title = 'Late night'", "--format", "paths"]);
+    assert!(std::fs::read_to_string(atom.trim())
+        .unwrap()
+        .contains("timestamp: 01/02/2026 23:30\n"));
+    assert_eq!(
+        notes.stdout(&["query", "timestamp = 2026-02-01", "--fields", "title"]),
+        "title\nLate night\n"
+    );
+    assert_eq!(
+        notes.stdout(&[
+            "query",
+            "timestamp >= 2026-02-01",
+            "--fields",
+            "title",
+            "--sort",
+            "timestamp",
+            "--reverse",
+        ]),
+        "title\nNext morning\nLate night\n"
+    );
+}
@@ -0,0 +1,135 @@
+//! Tests for rendering notes with `jot html` and `jot pdf`, and the themes and stylesheets they
+//! are rendered with.
+
+mod common;
+
+use std::{
+    fs,
+    io::Write,
+    process::{Output, Stdio},
+};
+
+use common::Notes;
+
+/// The head of the rendered document, where the theme and stylesheets go.
+fn head(html: &str) -> &str {
+    &html[..html.find("</head>").expect("No head in the output")]
+}
+
+/// Run jot with the text on stdin.
+fn pipe(notes: &Notes, args: &[&str], stdin: &str) -> Output {
+    let mut child = notes
+        .jot()
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Two notes, along with their paths as printed by jot query.
+fn queried() -> (Notes, String) {
+    let notes = Notes::new();
+    notes.write("a/first.md", "# First\n");
+    notes.write("b/second.md", "# Second\n");
+    let paths = notes.stdout(&["query", "title", "--format", "paths"]);
+
+    (notes, paths)
+}
+
+#[test]
+fn layers_stylesheets_from_config_front_matter_and_arguments() {
+    let notes = Notes::with_config("css = ['config.css']\n");
+    fs::write(notes.dir.path().join("config.css"), "/* config */").unwrap();
+    fs::write(notes.dir.path().join("arg.css"), "/* arg */").unwrap();
+    notes.write("styles/note.css", "/* note */");
+    let note = notes.write("note.md", "---\ncss: styles/note.css\n---\n# Styled\n");
+    let note = note.to_str().unwrap();
+
+    // Config files come first, then the note's own, then any passed as arguments
+    let html = notes.stdout(&["html", "-i", note, "--css", "arg.css"]);
+    let head = head(&html);
+    let order = ["/* config */", "/* note */", "/* arg */"].map(|css| head.find(css).unwrap());
+    assert!(order.is_sorted(), "{}", head);
+
+    let html = notes.stdout(&["html", "-i", note, "--css", "arg.css", "--no-css"]);
+    let head = self::head(&html);
+    assert!(head.contains("/* arg */") && !head.contains("/* config */"));
+    assert!(!head.contains("/* note */") && !head.contains("Jot default theme"));
+
+    let stderr = notes.stderr(&["html", "-i", note, "--css", "missing.css"]);
+    assert!(
+        stderr.contains("Stylesheets not found: missing.css"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn renders_with_the_configured_theme() {
+    let notes = Notes::with_config("theme = 'dark'\n");
+    let note = notes.write("note.md", "# Themed\n");
+    let note = note.to_str().unwrap();
+
+    let html = notes.stdout(&["html", "-i", note]);
+    assert!(head(&html).contains("Jot dark theme"));
+    assert!(head(&html).contains("Print rules shared by every built-in theme"));
+
+    let html = notes.stdout(&["html", "-i", note, "--theme", "serif"]);
+    assert!(head(&html).contains("Jot serif theme") && !head(&html).contains("Jot dark theme"));
+
+    let html = notes.stdout(&["html", "-i", note, "--theme", "none"]);
+    assert!(!head(&html).contains("<style>"));
+
+    let plain = Notes::new();
+    let note = plain.write("note.md", "# Themed\n");
+    let html = plain.stdout(&["html", "-i", note.to_str().unwrap()]);
+    assert!(head(&html).contains("Jot default theme"));
+}
+
+#[test]
+fn renders_each_note_listed_on_stdin() {
+    let (notes, paths) = queried();
+
+    let output = pipe(&notes, &["html", "--paths", "-o"], &paths);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(fs::read_to_string(notes.root.join("a/first.html"))
+        .unwrap()
+        .contains("First"));
+    assert!(notes.root.join("b/second.html").exists());
+
+    let out = notes.dir.path().join("out");
+    fs::create_dir(&out).unwrap();
+    let output = pipe(&notes, &["html", "--paths", "-o", "out"], &paths);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(out.join("first.html").exists() && out.join("second.html").exists());
+
+    let output = pipe(&notes, &["html", "--paths", "-o", "all.html"], &paths);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("give a directory instead"));
+}
+
+#[test]
+fn renders_pdfs_of_each_note_listed_on_stdin() {
+    if headless_chrome::browser::default_executable().is_err() {
+        eprintln!("Skipping, there is no Chrome-like browser to render PDFs with");
+        return;
+    }
+    let (notes, paths) = queried();
+
+    let output = pipe(&notes, &["pdf", "--paths", "-o"], &paths);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(output.stdout.is_empty());
+    assert!(fs::read(notes.root.join("a/first.pdf"))
+        .unwrap()
+        .starts_with(b"%PDF"));
+    assert!(notes.root.join("b/second.pdf").exists());
+}
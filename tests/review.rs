@@ -0,0 +1,58 @@
+//! Tests for writing review notes with `jot review`.
+
+mod common;
+
+use std::{fs, path::Path};
+
+use common::Notes;
+
+#[test]
+fn writes_review_of_atoms_since_date() {
+    let notes = Notes::new();
+    notes.write(
+        "atoms/2020/01/20200101_120000.md",
+        "---\ntags: [work]\n---\n# Launch\n\nShip it soon.\n\n- [ ] Call Sam\n- [x] Book room\n",
+    );
+
+    let path = notes.stdout(&["review", "--since", "2020-01-01"]);
+    let review = fs::read_to_string(path.trim()).unwrap();
+    assert!(path.trim().ends_with(".md"));
+    assert!(review.contains(
+        "- [Launch](../atoms/2020/01/20200101_120000.md) (2020-01-01, tags: work): Ship it soon."
+    ));
+    assert!(review.contains("- Call Sam, from [Launch](../atoms/2020/01/20200101_120000.md)"));
+    assert!(!review.contains("Book room"));
+}
+
+#[test]
+fn fills_in_the_template_once() {
+    let notes = Notes::with_config("[review]\ntemplate = 'notes/templates/review.md'\n");
+    notes.write(
+        "templates/review.md",
+        "# {{period}} ({{count}})\n\n{{atoms}}\n{{tasks}}{{ unknown }}\n",
+    );
+    notes.write(
+        "atoms/2020/01/20200101_120000.md",
+        "# Using {{count}} and {{tasks}}\n\nWrite {{end}} here.\n",
+    );
+
+    let path = notes.stdout(&["review", "--since", "2020-01-01"]);
+    let review = fs::read_to_string(path.trim()).unwrap();
+    assert!(review.starts_with("# 2020-01-01_"));
+    assert!(review.contains(" (1)\n"));
+    assert!(review.contains("- [Using {{count}} and {{tasks}}]("));
+    assert!(review.contains("Write {{end}} here."));
+    assert!(review.ends_with("{{ unknown }}\n"));
+}
+
+#[test]
+fn prints_only_the_review_path_when_rendering() {
+    let notes = Notes::new();
+    notes.write("atoms/2020/01/20200101_120000.md", "# Launch\n");
+
+    let stdout = notes.stdout(&["review", "--since", "2020-01-01", "--html"]);
+    let path = stdout.trim();
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(path.ends_with(".md"));
+    assert!(Path::new(path).with_extension("html").exists());
+}
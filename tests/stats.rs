@@ -0,0 +1,45 @@
+//! Tests for `jot stats`, which must count notes without their front matter.
+
+mod common;
+
+use common::Notes;
+
+#[test]
+fn counts_notes_tags_and_links() {
+    let notes = Notes::new();
+    notes.write(
+        "atoms/2020/01/20200101_120000.md",
+        "---\ntags: [work, ideas]\n---\none two three\n",
+    );
+    notes.write(
+        "atoms/2020/01/20200102_120000.md",
+        "---\ntags: [work]\n---\nSee [[20200101_120000]]\n",
+    );
+    notes.write("atoms/2020/01/20200104_120000.md", "#ideas\n");
+
+    let output = notes.run(&["stats", "--json", "--by", "day"]);
+
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["notes"], 3);
+    assert_eq!(stats["words"], 6);
+    assert_eq!(stats["first"], "2020-01-01");
+    assert_eq!(stats["last"], "2020-01-04");
+    assert_eq!(stats["streak"]["longest"], 2);
+    assert_eq!(stats["streak"]["current"], 0);
+    assert_eq!(
+        stats["periods"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|period| period["count"].as_u64().unwrap())
+            .collect::<Vec<_>>(),
+        [1, 1, 0, 1]
+    );
+    assert_eq!(stats["tags"][0]["name"], "ideas");
+    assert_eq!(stats["tags"][0]["count"], 2);
+    assert_eq!(
+        stats["linked"][0]["name"],
+        "atoms/2020/01/20200101_120000.md"
+    );
+    assert_eq!(stats["linked"][0]["count"], 1);
+}
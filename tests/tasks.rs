@@ -0,0 +1,54 @@
+//! Tests for listing and completing the tasks in notes with `jot tasks`.
+
+mod common;
+
+use common::Notes;
+
+const NOTE: &str = "atoms/2024/01/20240101_120000.md";
+
+/// Build a notes root with a note holding several tasks.
+fn setup() -> Notes {
+    let notes = Notes::new();
+    notes.write(
+        NOTE,
+        "---\ntags: [work]\n---\n# Plan\n\n- [ ] Call Sam @due(2000-01-01) !!!\n- [x] Book room\n  - [ ] Send agenda #email 📅 2999-12-31\n\n```\n- [ ] Not a task\n```\n",
+    );
+
+    notes
+}
+
+#[test]
+fn lists_and_filters_tasks() {
+    let notes = setup();
+
+    assert_eq!(
+        notes.stdout(&["tasks"]),
+        "20240101_120000:6  [ ] !!! Call Sam (due 2000-01-01)\n20240101_120000:8  [ ] Send agenda #email (due 2999-12-31)\n"
+    );
+    assert_eq!(
+        notes.stdout(&["tasks", "--done"]),
+        "20240101_120000:7  [x] Book room\n"
+    );
+    assert_eq!(
+        notes.stdout(&["tasks", "--tag", "email"]),
+        "20240101_120000:8  [ ] Send agenda #email (due 2999-12-31)\n"
+    );
+    assert_eq!(notes.stdout(&["tasks", "--tag", "home"]), "");
+    assert_eq!(
+        notes.stdout(&["tasks", "agenda"]),
+        "Overdue\n  20240101_120000:6  [ ] !!! Call Sam (due 2000-01-01)\n\nUpcoming\n  20240101_120000:8  [ ] Send agenda #email (due 2999-12-31)\n"
+    );
+}
+
+#[test]
+fn ticks_tasks_in_place() {
+    let notes = setup();
+    let before = notes.read(NOTE);
+
+    notes.stdout(&["tasks", "done", "20240101_120000:8"]);
+
+    assert_eq!(
+        notes.read(NOTE),
+        before.replace("  - [ ] Send", "  - [x] Send")
+    );
+}
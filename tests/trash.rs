@@ -0,0 +1,102 @@
+//! Tests for removing notes to the trash with `jot rm`, restoring them with `jot trash`, and moving
+//! atoms to the archive with `jot archive`.
+
+mod common;
+
+use std::fs;
+
+use common::Notes;
+
+/// The IDs of the notes in the trash, in the order they were removed.
+fn trashed(notes: &Notes) -> Vec<String> {
+    notes
+        .stdout(&["trash", "list"])
+        .lines()
+        .map(|line| line.split_whitespace().next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn restores_removed_notes_to_where_they_were() {
+    let notes = Notes::new();
+    notes.write("projects/plan.md", "# Plan\n");
+
+    let stdout = notes.stdout(&["rm", "projects/plan.md"]);
+    assert!(stdout.starts_with("Moved projects/plan.md to the trash"));
+    assert!(!notes.root.join("projects/plan.md").exists());
+
+    let ids = trashed(&notes);
+    assert_eq!(ids.len(), 1);
+    assert!(ids[0].ends_with("_plan.md"));
+    assert_eq!(notes.read(&format!(".trash/{}", ids[0])), "# Plan\n");
+
+    notes.run(&["trash", "restore", "plan"]);
+    assert_eq!(notes.read("projects/plan.md"), "# Plan\n");
+    assert!(trashed(&notes).is_empty());
+}
+
+#[test]
+fn keeps_notes_with_the_same_name_apart() {
+    let notes = Notes::new();
+    notes.write("a/note.md", "# A\n");
+    notes.write("b/note.md", "# B\n");
+
+    // Both are removed in the same second, so they would have the same name in the trash
+    notes.run(&["rm", "a/note.md", "b/note.md"]);
+    let ids = trashed(&notes);
+    assert_eq!(ids.len(), 2);
+    assert_ne!(ids[0], ids[1]);
+
+    // Restoring by name alone is ambiguous, so each is restored by its ID
+    let stderr = notes.stderr(&["trash", "restore", "note.md"]);
+    assert!(
+        stderr.contains("2 notes in the trash match 'note.md'"),
+        "{}",
+        stderr
+    );
+    notes.run(&["trash", "restore", &ids[1]]);
+    notes.run(&["trash", "restore", &ids[0]]);
+    assert_eq!(notes.read("a/note.md"), "# A\n");
+    assert_eq!(notes.read("b/note.md"), "# B\n");
+}
+
+#[test]
+fn refuses_to_restore_over_a_new_note() {
+    let notes = Notes::new();
+    notes.write("plan.md", "# Old plan\n");
+    notes.run(&["rm", "plan.md"]);
+    notes.write("plan.md", "# New plan\n");
+
+    let stderr = notes.stderr(&["trash", "restore", "plan.md"]);
+    assert!(stderr.contains("plan.md already exists"), "{}", stderr);
+    assert_eq!(notes.read("plan.md"), "# New plan\n");
+    assert_eq!(trashed(&notes).len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn archives_atoms_by_year_and_month() {
+    let notes = Notes::new();
+    notes.write("atoms/2024/01/20240101_120000.md", "# Old\n");
+    notes.write("atoms/2025/02/20250201_120000.md", "# New\n");
+
+    // Reach the notes root through a link, which the archive must see through like the root
+    let link = notes.dir.path().join("link");
+    std::os::unix::fs::symlink(&notes.root, &link).unwrap();
+    fs::write(
+        &notes.config,
+        format!("root = '{}'\n", link.to_string_lossy()),
+    )
+    .unwrap();
+
+    assert_eq!(
+        notes.stdout(&["archive", "--before", "2025-01-01"]),
+        "Archived atoms/2024/01/20240101_120000.md to archive/2024/01/20240101_120000.md\n"
+    );
+    assert_eq!(notes.read("archive/2024/01/20240101_120000.md"), "# Old\n");
+    assert!(!notes.root.join("atoms/2024/01/20240101_120000.md").exists());
+    assert!(notes.root.join("atoms/2025/02/20250201_120000.md").exists());
+
+    let stderr = notes.stderr(&["archive", "archive/2024/01/20240101_120000.md"]);
+    assert!(stderr.contains("is not an atom"), "{}", stderr);
+}